@@ -0,0 +1,122 @@
+//! `--edit-ignores`, picking what to ignore or stop at in the rendered tree and saving it to `.project-tree.toml`.
+
+use crate::config;
+use crate::entry::Entry;
+use crate::format::{Format, RenderOptions};
+use crate::tui::{Key, Terminal};
+use std::io;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Shown,
+    Ignored,
+    Stopped,
+}
+
+/// A tree line with the entry it shows
+struct Line {
+    text: String,
+    path: String,
+    is_dir: bool,
+    /// Index of the parent line
+    parent: Option<usize>,
+}
+
+const HELP: &str = "↑/↓ move  space/i ignore  s stop at  enter save  q cancel";
+
+/// Runs the picker, returning the number of entries saved, `None` when cancelled
+pub fn run(entries: &[Entry]) -> io::Result<Option<usize>> {
+    let rendered: String = Format::Text.render(entries, None, &RenderOptions::default());
+    let mut lines: Vec<Line> = Vec::new();
+    flatten(entries, None, &mut lines);
+    for (line, text) in lines.iter_mut().zip(rendered.lines()) {
+        line.text = text.to_owned();
+    }
+    if lines.is_empty() {
+        return Ok(Some(0));
+    }
+
+    let mut marks: Vec<Mark> = vec![Mark::Shown; lines.len()];
+    let mut cursor: usize = 0;
+    let mut scroll: usize = 0;
+    let mut terminal: Terminal = Terminal::open()?;
+    loop {
+        let (width, height) = terminal.size();
+        let visible: usize = height.saturating_sub(2).max(1);
+        if cursor < scroll { scroll = cursor; }
+        if cursor >= scroll + visible { scroll = cursor + 1 - visible; }
+
+        let mut screen: Vec<String> = vec![HELP.to_owned(), String::new()];
+        for (i, line) in lines.iter().enumerate().skip(scroll).take(visible) {
+            screen.push(draw_line(line, marks[i], is_hidden(&lines, &marks, i), i == cursor, width));
+        }
+        terminal.draw(&screen)?;
+
+        match terminal.read_key()? {
+            Key::Up | Key::Char('k') => cursor = cursor.saturating_sub(1),
+            Key::Down | Key::Char('j') => cursor = (cursor + 1).min(lines.len() - 1),
+            Key::PageUp => cursor = cursor.saturating_sub(visible),
+            Key::PageDown => cursor = (cursor + visible).min(lines.len() - 1),
+            Key::Home => cursor = 0,
+            Key::End => cursor = lines.len() - 1,
+            Key::Char(' ') | Key::Char('i') => toggle(&mut marks[cursor], Mark::Ignored),
+            Key::Char('s') if lines[cursor].is_dir => toggle(&mut marks[cursor], Mark::Stopped),
+            Key::Enter | Key::Char('w') => break,
+            Key::Escape | Key::Char('q') => return Ok(None),
+            _ => {}
+        }
+    }
+    drop(terminal);
+
+    //Below a marked directory everything is gone anyway
+    let pick = |mark: Mark| -> Vec<String> {
+        (0..lines.len())
+            .filter(|&i| marks[i] == mark && !is_hidden(&lines, &marks, i))
+            .map(|i| config_entry(&lines[i].path))
+            .collect()
+    };
+    let (ignore, stop) = (pick(Mark::Ignored), pick(Mark::Stopped));
+    config::add_entries(&ignore, &stop)?;
+    Ok(Some(ignore.len() + stop.len()))
+}
+
+fn flatten(entries: &[Entry], parent: Option<usize>, lines: &mut Vec<Line>) {
+    for entry in entries {
+        let index: usize = lines.len();
+        lines.push(Line { text: String::new(), path: entry.path.clone(), is_dir: entry.is_dir, parent });
+        flatten(&entry.children, Some(index), lines);
+    }
+}
+
+fn toggle(mark: &mut Mark, to: Mark) {
+    *mark = if *mark == to { Mark::Shown } else { to };
+}
+
+/// Whether a directory above the line is ignored or stopped at
+fn is_hidden(lines: &[Line], marks: &[Mark], i: usize) -> bool {
+    let mut parent: Option<usize> = lines[i].parent;
+    while let Some(p) = parent {
+        if marks[p] != Mark::Shown { return true; }
+        parent = lines[p].parent;
+    }
+    false
+}
+
+fn draw_line(line: &Line, mark: Mark, hidden: bool, selected: bool, width: usize) -> String {
+    let suffix: &str = match mark {
+        Mark::Shown => "",
+        Mark::Ignored => "  [ignore]",
+        Mark::Stopped => "  [stop]",
+    };
+    let text: String = crate::style::truncate(&format!("{}{suffix}", line.text), width);
+    match (selected, hidden || mark == Mark::Ignored) {
+        (true, _) => format!("\x1b[7m{text}\x1b[0m"),
+        (false, true) => format!("\x1b[2m{text}\x1b[0m"),
+        (false, false) => text,
+    }
+}
+
+/// Top level entries get a `./` so they stay paths from the root instead of names matched at any depth
+fn config_entry(path: &str) -> String {
+    if path.contains('/') { path.to_owned() } else { format!("./{path}") }
+}
@@ -0,0 +1,159 @@
+//! `--cache`, directory listings kept between runs under `~/.cache/project-tree`.
+//!
+//! Adding, removing or renaming an entry changes its directory's mtime, so a directory with the same mtime as last time
+//! still has the same entries and doesn't have to be read or its entries stat'ed again. Only the raw listing is kept,
+//! the ignore/stop rules are applied after, so one cache serves every combination of flags.
+
+use crate::format::json_string;
+use crate::json::Json;
+use crate::platform;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Bumped when the file layout changes, older caches are ignored
+const VERSION: f64 = 1.0;
+
+/// A directory changed twice within the mtime's resolution would look unchanged, so recently changed ones aren't kept
+const SETTLE: Duration = Duration::from_secs(2);
+
+/// An entry of a directory, with what the scan needs to know without looking at it again
+#[derive(Clone, Debug)]
+pub struct Child {
+    pub path: PathBuf,
+    /// Follows links, like `Path::is_dir`
+    pub is_dir: bool,
+    pub is_link: bool,
+    /// FIFO, socket or device
+    pub is_special: bool,
+}
+
+impl Child {
+    pub fn stat(path: PathBuf) -> Child {
+        Child {
+            is_dir: path.is_dir(),
+            is_link: platform::is_link(&path),
+            is_special: platform::special_kind(&path).is_some(),
+            path,
+        }
+    }
+
+    /// `d`, `l` and `s` for the flags, `f` when none are set
+    fn flags(&self) -> String {
+        let flags: String = [(self.is_dir, 'd'), (self.is_link, 'l'), (self.is_special, 's')].iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, flag)| flag)
+            .collect();
+        if flags.is_empty() { String::from("f") } else { flags }
+    }
+}
+
+struct Cached {
+    /// Nanoseconds since the epoch
+    mtime: u128,
+    /// Names and flags
+    children: Vec<(String, String)>,
+}
+
+pub struct Cache {
+    file: PathBuf,
+    dirs: HashMap<String, Cached>,
+    /// Directories looked at this run, the others are dropped when saving so deleted ones don't pile up
+    seen: HashSet<String>,
+    changed: bool,
+}
+
+impl Cache {
+    /// The cache for the project at `root`, empty if there is none yet or it can't be read
+    pub fn load(root: &Path) -> Option<Cache> {
+        let root: PathBuf = fs::canonicalize(root).ok()?;
+        let file: PathBuf = cache_dir()?.join(format!("{:016x}.json", fnv1a(root.to_string_lossy().as_bytes())));
+        let dirs: HashMap<String, Cached> = fs::read_to_string(&file).ok()
+            .and_then(|text| Json::parse(&text))
+            .and_then(|json| parse(&json))
+            .unwrap_or_default();
+        Some(Cache { file, dirs, seen: HashSet::new(), changed: false })
+    }
+
+    /// Entries of `dir` from the cache if it hasn't changed, otherwise from `read` (and remembered for next time)
+    pub fn list(&mut self, dir: &Path, read: impl FnOnce() -> io::Result<Vec<PathBuf>>) -> io::Result<Vec<Child>> {
+        let key: String = dir.to_string_lossy().into_owned();
+        let modified: Option<SystemTime> = fs::metadata(dir).and_then(|metadata| metadata.modified()).ok();
+        let mtime: Option<u128> = modified.and_then(|time| time.duration_since(UNIX_EPOCH).ok()).map(|since| since.as_nanos());
+        self.seen.insert(key.clone());
+
+        if let Some(cached) = self.dirs.get(&key).filter(|cached| Some(cached.mtime) == mtime) {
+            return Ok(cached.children.iter().map(|(name, flags)| Child {
+                path: dir.join(name),
+                is_dir: flags.contains('d'),
+                is_link: flags.contains('l'),
+                is_special: flags.contains('s'),
+            }).collect());
+        }
+
+        let children: Vec<Child> = read()?.into_iter().map(Child::stat).collect();
+        let settled: bool = modified.is_some_and(|time| time.elapsed().is_ok_and(|age| age > SETTLE));
+        //Names that aren't UTF-8 wouldn't survive the round trip through JSON
+        let names: Option<Vec<String>> = children.iter().map(|child| Some(child.path.file_name()?.to_str()?.to_owned())).collect();
+        match (mtime, names) {
+            (Some(mtime), Some(names)) if settled => {
+                let children: Vec<(String, String)> = names.into_iter().zip(children.iter().map(Child::flags)).collect();
+                self.dirs.insert(key, Cached { mtime, children });
+            }
+            _ => { self.dirs.remove(&key); }
+        }
+        self.changed = true;
+        Ok(children)
+    }
+
+    /// Writes the cache back if anything changed, failing quietly like a cache should
+    pub fn save(&mut self) {
+        let dropped: bool = self.dirs.keys().any(|key| !self.seen.contains(key));
+        if !self.changed && !dropped { return; }
+        self.dirs.retain(|key, _| self.seen.contains(key));
+
+        let dirs: Vec<String> = self.dirs.iter().map(|(key, cached)| {
+            let children: Vec<String> = cached.children.iter()
+                .map(|(name, flags)| format!("[{},\"{flags}\"]", json_string(name)))
+                .collect();
+            format!("[{},\"{}\",[{}]]", json_string(key), cached.mtime, children.join(","))
+        }).collect();
+        let text: String = format!("{{\"version\":{VERSION},\"dirs\":[\n{}\n]}}\n", dirs.join(",\n"));
+        if let Some(parent) = self.file.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let _ = fs::write(&self.file, text);
+        self.changed = false;
+    }
+}
+
+/// `{"version":1,"dirs":[[path, mtime, [[name, flags], ...]], ...]}`
+fn parse(json: &Json) -> Option<HashMap<String, Cached>> {
+    if json.get("version")?.as_number()? != VERSION {
+        return None;
+    }
+    json.get("dirs")?.as_array()?.iter().map(|dir| {
+        let [path, mtime, children] = dir.as_array()? else { return None };
+        let children: Vec<(String, String)> = children.as_array()?.iter().map(|child| {
+            let [name, flags] = child.as_array()? else { return None };
+            Some((name.as_str()?.to_owned(), flags.as_str()?.to_owned()))
+        }).collect::<Option<_>>()?;
+        Some((path.as_str()?.to_owned(), Cached { mtime: mtime.as_str()?.parse().ok()?, children }))
+    }).collect()
+}
+
+/// `$XDG_CACHE_HOME/project-tree`, `~/.cache/project-tree`, or `%LOCALAPPDATA%\project-tree` on Windows
+fn cache_dir() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let base: PathBuf = var("XDG_CACHE_HOME")
+        .or_else(|| var("HOME").map(|home| home.join(".cache")))
+        .or_else(|| var("LOCALAPPDATA"))?;
+    Some(base.join("project-tree"))
+}
+
+/// Stable across runs and Rust versions, unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
@@ -0,0 +1,54 @@
+//! What sort of entry something is on disk, which is what `ls` colors by.
+
+use crate::platform;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    Dir,
+    File,
+    Executable,
+    Link,
+    /// A link whose target doesn't exist
+    BrokenLink,
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+}
+
+impl Class {
+    /// `path` itself, not what a link points to
+    pub fn of(path: &Path) -> Class {
+        if platform::is_link(path) {
+            //exists() follows the link
+            return if path.exists() { Class::Link } else { Class::BrokenLink };
+        }
+        Class::of_target(path)
+    }
+
+    /// What `path` leads to, following links
+    pub fn of_target(path: &Path) -> Class {
+        let Ok(metadata) = fs::metadata(path) else { return Class::BrokenLink };
+        if metadata.is_dir() {
+            return Class::Dir;
+        }
+        if platform::is_executable(&metadata) {
+            return Class::Executable;
+        }
+        match platform::special_kind(path) {
+            Some("fifo") => Class::Fifo,
+            Some("socket") => Class::Socket,
+            Some("block device") => Class::BlockDevice,
+            Some("char device") => Class::CharDevice,
+            _ if cfg!(windows) && is_windows_executable(path) => Class::Executable,
+            _ => Class::File,
+        }
+    }
+}
+
+fn is_windows_executable(path: &Path) -> bool {
+    let extension: String = path.extension().unwrap_or_default().to_string_lossy().to_ascii_lowercase();
+    ["exe", "com", "bat", "cmd", "ps1"].contains(&extension.as_str())
+}
@@ -0,0 +1,193 @@
+//! Clipboard backends.
+//!
+//! The `clipboard` crate only speaks X11, so on Wayland sessions we hand the tree to `wl-copy`
+//! (from wl-clipboard) and only fall back to X11 if that isn't available. When there is no
+//! system clipboard at all (SSH, containers) the tree can be sent to the terminal via OSC 52.
+//!
+//! Rich HTML has to be offered next to the plain text, which `wl-copy`, `xclip` and the X11 crate
+//! can't do (they offer one type each), so for that we own the X11 selection ourselves. XWayland
+//! hands both on to Wayland apps.
+//!
+//! The X11 backend is behind the `x11-clipboard` feature (on by default) since it links against xcb.
+
+use clap::ValueEnum;
+#[cfg(feature = "x11-clipboard")]
+use clipboard::{ClipboardContext, ClipboardProvider};
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+type ClipResult = Result<(), Box<dyn Error>>;
+
+/// Where the tree gets copied to
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// System clipboard, or OSC 52 over SSH / when no system clipboard is available
+    #[default]
+    Auto,
+    /// System clipboard only (wl-copy on Wayland, X11 otherwise)
+    System,
+    /// OSC 52 terminal escape, lands on the clipboard of the machine running the terminal
+    Osc52,
+}
+
+/// Copies `contents` to the clipboard using `backend`
+pub fn copy(contents: String, backend: Backend) -> ClipResult {
+    match backend {
+        Backend::System => copy_system(contents),
+        Backend::Osc52 => copy_osc52(&contents),
+        Backend::Auto if is_ssh() => copy_osc52(&contents),
+        Backend::Auto => copy_system(contents.clone()).or_else(|_| copy_osc52(&contents)),
+    }
+}
+
+/// Copies `text` together with an `html` rendering of it, which rich editors (Google Docs, Confluence) paste with its
+/// layout and styling. Where only text can be offered (OSC 52, no X11) just the text is copied
+pub fn copy_rich(text: String, html: &str, backend: Backend) -> ClipResult {
+    let osc52: bool = backend == Backend::Osc52 || (backend == Backend::Auto && is_ssh());
+    if !osc52 && copy_x11_rich(&text, html).is_ok() {
+        return Ok(());
+    }
+    copy(text, backend)
+}
+
+fn copy_system(contents: String) -> ClipResult {
+    if is_wayland() && copy_command("wl-copy", &[], &contents).is_ok() {
+        return Ok(());
+    }
+    copy_x11(contents)
+}
+
+fn is_wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some_and(|display| !display.is_empty())
+}
+
+fn is_ssh() -> bool {
+    std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some()
+}
+
+/// Pipes `contents` into a clipboard tool. wl-copy and xclip fork into the background to serve the selection, so they outlive us
+fn copy_command(program: &str, args: &[&str], contents: &str) -> ClipResult {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    child.stdin.take().ok_or(format!("{program} has no stdin"))?.write_all(contents.as_bytes())?;
+
+    if child.wait()?.success() { Ok(()) } else { Err(format!("{program} failed").into()) }
+}
+
+#[cfg(feature = "x11-clipboard")]
+fn copy_x11(contents: String) -> ClipResult {
+    let mut clipboard: ClipboardContext = ClipboardProvider::new()?;
+    clipboard.set_contents(contents)
+}
+
+#[cfg(not(feature = "x11-clipboard"))]
+fn copy_x11(_contents: String) -> ClipResult {
+    Err("built without the x11-clipboard feature".into())
+}
+
+/// Takes the X11 clipboard and serves it from a forked child, like xclip does, since the selection only lives as long
+/// as its owner. The child exits once something else is copied
+#[cfg(all(feature = "x11-clipboard", unix, not(any(target_os = "macos", target_os = "android"))))]
+fn copy_x11_rich(text: &str, html: &str) -> ClipResult {
+    let (connection, screen) = xcb::Connection::connect(None)?;
+    let atom = |name: &str| -> Result<xcb::Atom, xcb::GenericError> { Ok(xcb::intern_atom(&connection, false, name).get_reply()?.atom()) };
+    let clipboard: xcb::Atom = atom("CLIPBOARD")?;
+    let targets: xcb::Atom = atom("TARGETS")?;
+    let html_type: xcb::Atom = atom("text/html")?;
+    let text_types: [xcb::Atom; 4] = [atom("UTF8_STRING")?, atom("text/plain;charset=utf-8")?, atom("text/plain")?, xcb::ATOM_STRING];
+    //Without INCR transfers each has to fit in one request
+    let max_length: usize = connection.get_maximum_request_length() as usize * 4 - 24;
+    if text.len().max(html.len()) > max_length {
+        return Err("too big for a single X11 transfer".into());
+    }
+
+    let window: xcb::Window = connection.generate_id();
+    let root = connection.get_setup().roots().nth(screen as usize).ok_or("no X11 screen")?;
+    xcb::create_window(&connection, xcb::COPY_FROM_PARENT as u8, window, root.root(), 0, 0, 1, 1, 0,
+        xcb::WINDOW_CLASS_INPUT_OUTPUT as u16, root.root_visual(), &[]);
+    xcb::set_selection_owner(&connection, window, clipboard, xcb::CURRENT_TIME);
+    if xcb::get_selection_owner(&connection, clipboard).get_reply()?.owner() != window {
+        return Err("could not take the X11 clipboard".into());
+    }
+
+    match unsafe { libc::fork() } {
+        -1 => Err(std::io::Error::last_os_error().into()),
+        0 => {
+            unsafe { libc::setsid() };
+            while let Some(event) = connection.wait_for_event() {
+                match event.response_type() & !0x80 {
+                    xcb::SELECTION_REQUEST => {
+                        let request: &xcb::SelectionRequestEvent = unsafe { xcb::cast_event(&event) };
+                        let mut property: xcb::Atom = request.property();
+                        if request.target() == targets {
+                            let offered: Vec<xcb::Atom> = [targets, html_type].iter().chain(&text_types).copied().collect();
+                            xcb::change_property(&connection, xcb::PROP_MODE_REPLACE as u8, request.requestor(), property, xcb::ATOM_ATOM, 32, &offered);
+                        } else if request.target() == html_type {
+                            xcb::change_property(&connection, xcb::PROP_MODE_REPLACE as u8, request.requestor(), property, html_type, 8, html.as_bytes());
+                        } else if text_types.contains(&request.target()) {
+                            xcb::change_property(&connection, xcb::PROP_MODE_REPLACE as u8, request.requestor(), property, request.target(), 8, text.as_bytes());
+                        } else {
+                            //Refused
+                            property = xcb::NONE;
+                        }
+                        let notify = xcb::SelectionNotifyEvent::new(request.time(), request.requestor(), request.selection(), request.target(), property);
+                        xcb::send_event(&connection, false, request.requestor(), 0, &notify);
+                        connection.flush();
+                    }
+                    xcb::SELECTION_CLEAR => break,
+                    _ => {}
+                }
+            }
+            unsafe { libc::_exit(0) }
+        }
+        //The child has the connection now
+        _ => {
+            std::mem::forget(connection);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(all(feature = "x11-clipboard", unix, not(any(target_os = "macos", target_os = "android")))))]
+fn copy_x11_rich(_text: &str, _html: &str) -> ClipResult {
+    Err("rich copies need the x11-clipboard feature on X11".into())
+}
+
+/// Writes the OSC 52 "set clipboard" escape straight to the terminal, so it still works when stdout is redirected.
+/// Fails when there is no controlling terminal (CI), rather than spraying the escape into a log.
+fn copy_osc52(contents: &str) -> ClipResult {
+    let mut sequence: String = format!("\x1b]52;c;{}\x07", base64(contents.as_bytes()));
+
+    //tmux swallows unknown escapes unless they are wrapped in its passthrough DCS
+    if std::env::var_os("TMUX").is_some() {
+        sequence = format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"));
+    }
+
+    let mut tty = OpenOptions::new().write(true).open("/dev/tty").map_err(|_| "no terminal to send OSC 52 to")?;
+    tty.write_all(sequence.as_bytes())?;
+    Ok(())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded: String = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n: u32 = chunk.iter().enumerate().fold(0, |n, (i, &byte)| n | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
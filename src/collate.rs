@@ -0,0 +1,190 @@
+//! Name ordering, and NFC normalization so the same tree renders identically on macOS (which hands out
+//! decomposed names) and Linux.
+//!
+//! There is no normalization crate to lean on, so the composition table only covers the precomposed
+//! Latin letters (Latin-1 through Latin Extended-B, plus Latin Extended Additional for Vietnamese),
+//! which is what shows up in file names in practice.
+
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Collation {
+    /// Plain byte order, uppercase before lowercase
+    #[default]
+    Bytes,
+    /// Names normalized to NFC, ordered ignoring case and accents (é next to e) and shown normalized
+    Unicode,
+}
+
+impl Collation {
+    /// How a name is shown
+    pub fn display(self, name: &str) -> String {
+        match self {
+            Collation::Bytes => name.to_owned(),
+            Collation::Unicode => nfc(name),
+        }
+    }
+
+    /// What names are ordered by, worth computing once per name with `sort_by_cached_key`
+    pub fn key(self, name: &str) -> (String, String) {
+        match self {
+            Collation::Bytes => (name.to_owned(), String::new()),
+            //Accents and case only break ties, so `Écoles` sorts between `ecole` and `eden`
+            Collation::Unicode => (sort_key(name), nfc(name)),
+        }
+    }
+}
+
+/// Precomposed character, base, combining mark
+const COMPOSITIONS: &[(char, char, char)] = &[
+    ('À', 'A', '\u{300}'), ('Á', 'A', '\u{301}'), ('Â', 'A', '\u{302}'), ('Ã', 'A', '\u{303}'), ('Ä', 'A', '\u{308}'), ('Å', 'A', '\u{30A}'),
+    ('Ç', 'C', '\u{327}'), ('È', 'E', '\u{300}'), ('É', 'E', '\u{301}'), ('Ê', 'E', '\u{302}'), ('Ë', 'E', '\u{308}'), ('Ì', 'I', '\u{300}'),
+    ('Í', 'I', '\u{301}'), ('Î', 'I', '\u{302}'), ('Ï', 'I', '\u{308}'), ('Ñ', 'N', '\u{303}'), ('Ò', 'O', '\u{300}'), ('Ó', 'O', '\u{301}'),
+    ('Ô', 'O', '\u{302}'), ('Õ', 'O', '\u{303}'), ('Ö', 'O', '\u{308}'), ('Ù', 'U', '\u{300}'), ('Ú', 'U', '\u{301}'), ('Û', 'U', '\u{302}'),
+    ('Ü', 'U', '\u{308}'), ('Ý', 'Y', '\u{301}'), ('à', 'a', '\u{300}'), ('á', 'a', '\u{301}'), ('â', 'a', '\u{302}'), ('ã', 'a', '\u{303}'),
+    ('ä', 'a', '\u{308}'), ('å', 'a', '\u{30A}'), ('ç', 'c', '\u{327}'), ('è', 'e', '\u{300}'), ('é', 'e', '\u{301}'), ('ê', 'e', '\u{302}'),
+    ('ë', 'e', '\u{308}'), ('ì', 'i', '\u{300}'), ('í', 'i', '\u{301}'), ('î', 'i', '\u{302}'), ('ï', 'i', '\u{308}'), ('ñ', 'n', '\u{303}'),
+    ('ò', 'o', '\u{300}'), ('ó', 'o', '\u{301}'), ('ô', 'o', '\u{302}'), ('õ', 'o', '\u{303}'), ('ö', 'o', '\u{308}'), ('ù', 'u', '\u{300}'),
+    ('ú', 'u', '\u{301}'), ('û', 'u', '\u{302}'), ('ü', 'u', '\u{308}'), ('ý', 'y', '\u{301}'), ('ÿ', 'y', '\u{308}'), ('Ā', 'A', '\u{304}'),
+    ('ā', 'a', '\u{304}'), ('Ă', 'A', '\u{306}'), ('ă', 'a', '\u{306}'), ('Ą', 'A', '\u{328}'), ('ą', 'a', '\u{328}'), ('Ć', 'C', '\u{301}'),
+    ('ć', 'c', '\u{301}'), ('Ĉ', 'C', '\u{302}'), ('ĉ', 'c', '\u{302}'), ('Ċ', 'C', '\u{307}'), ('ċ', 'c', '\u{307}'), ('Č', 'C', '\u{30C}'),
+    ('č', 'c', '\u{30C}'), ('Ď', 'D', '\u{30C}'), ('ď', 'd', '\u{30C}'), ('Ē', 'E', '\u{304}'), ('ē', 'e', '\u{304}'), ('Ĕ', 'E', '\u{306}'),
+    ('ĕ', 'e', '\u{306}'), ('Ė', 'E', '\u{307}'), ('ė', 'e', '\u{307}'), ('Ę', 'E', '\u{328}'), ('ę', 'e', '\u{328}'), ('Ě', 'E', '\u{30C}'),
+    ('ě', 'e', '\u{30C}'), ('Ĝ', 'G', '\u{302}'), ('ĝ', 'g', '\u{302}'), ('Ğ', 'G', '\u{306}'), ('ğ', 'g', '\u{306}'), ('Ġ', 'G', '\u{307}'),
+    ('ġ', 'g', '\u{307}'), ('Ģ', 'G', '\u{327}'), ('ģ', 'g', '\u{327}'), ('Ĥ', 'H', '\u{302}'), ('ĥ', 'h', '\u{302}'), ('Ĩ', 'I', '\u{303}'),
+    ('ĩ', 'i', '\u{303}'), ('Ī', 'I', '\u{304}'), ('ī', 'i', '\u{304}'), ('Ĭ', 'I', '\u{306}'), ('ĭ', 'i', '\u{306}'), ('Į', 'I', '\u{328}'),
+    ('į', 'i', '\u{328}'), ('İ', 'I', '\u{307}'), ('Ĵ', 'J', '\u{302}'), ('ĵ', 'j', '\u{302}'), ('Ķ', 'K', '\u{327}'), ('ķ', 'k', '\u{327}'),
+    ('Ĺ', 'L', '\u{301}'), ('ĺ', 'l', '\u{301}'), ('Ļ', 'L', '\u{327}'), ('ļ', 'l', '\u{327}'), ('Ľ', 'L', '\u{30C}'), ('ľ', 'l', '\u{30C}'),
+    ('Ń', 'N', '\u{301}'), ('ń', 'n', '\u{301}'), ('Ņ', 'N', '\u{327}'), ('ņ', 'n', '\u{327}'), ('Ň', 'N', '\u{30C}'), ('ň', 'n', '\u{30C}'),
+    ('Ō', 'O', '\u{304}'), ('ō', 'o', '\u{304}'), ('Ŏ', 'O', '\u{306}'), ('ŏ', 'o', '\u{306}'), ('Ő', 'O', '\u{30B}'), ('ő', 'o', '\u{30B}'),
+    ('Ŕ', 'R', '\u{301}'), ('ŕ', 'r', '\u{301}'), ('Ŗ', 'R', '\u{327}'), ('ŗ', 'r', '\u{327}'), ('Ř', 'R', '\u{30C}'), ('ř', 'r', '\u{30C}'),
+    ('Ś', 'S', '\u{301}'), ('ś', 's', '\u{301}'), ('Ŝ', 'S', '\u{302}'), ('ŝ', 's', '\u{302}'), ('Ş', 'S', '\u{327}'), ('ş', 's', '\u{327}'),
+    ('Š', 'S', '\u{30C}'), ('š', 's', '\u{30C}'), ('Ţ', 'T', '\u{327}'), ('ţ', 't', '\u{327}'), ('Ť', 'T', '\u{30C}'), ('ť', 't', '\u{30C}'),
+    ('Ũ', 'U', '\u{303}'), ('ũ', 'u', '\u{303}'), ('Ū', 'U', '\u{304}'), ('ū', 'u', '\u{304}'), ('Ŭ', 'U', '\u{306}'), ('ŭ', 'u', '\u{306}'),
+    ('Ů', 'U', '\u{30A}'), ('ů', 'u', '\u{30A}'), ('Ű', 'U', '\u{30B}'), ('ű', 'u', '\u{30B}'), ('Ų', 'U', '\u{328}'), ('ų', 'u', '\u{328}'),
+    ('Ŵ', 'W', '\u{302}'), ('ŵ', 'w', '\u{302}'), ('Ŷ', 'Y', '\u{302}'), ('ŷ', 'y', '\u{302}'), ('Ÿ', 'Y', '\u{308}'), ('Ź', 'Z', '\u{301}'),
+    ('ź', 'z', '\u{301}'), ('Ż', 'Z', '\u{307}'), ('ż', 'z', '\u{307}'), ('Ž', 'Z', '\u{30C}'), ('ž', 'z', '\u{30C}'), ('Ơ', 'O', '\u{31B}'),
+    ('ơ', 'o', '\u{31B}'), ('Ư', 'U', '\u{31B}'), ('ư', 'u', '\u{31B}'), ('Ǎ', 'A', '\u{30C}'), ('ǎ', 'a', '\u{30C}'), ('Ǐ', 'I', '\u{30C}'),
+    ('ǐ', 'i', '\u{30C}'), ('Ǒ', 'O', '\u{30C}'), ('ǒ', 'o', '\u{30C}'), ('Ǔ', 'U', '\u{30C}'), ('ǔ', 'u', '\u{30C}'), ('Ǖ', 'Ü', '\u{304}'),
+    ('ǖ', 'ü', '\u{304}'), ('Ǘ', 'Ü', '\u{301}'), ('ǘ', 'ü', '\u{301}'), ('Ǚ', 'Ü', '\u{30C}'), ('ǚ', 'ü', '\u{30C}'), ('Ǜ', 'Ü', '\u{300}'),
+    ('ǜ', 'ü', '\u{300}'), ('Ǟ', 'Ä', '\u{304}'), ('ǟ', 'ä', '\u{304}'), ('Ǡ', 'Ȧ', '\u{304}'), ('ǡ', 'ȧ', '\u{304}'), ('Ǣ', 'Æ', '\u{304}'),
+    ('ǣ', 'æ', '\u{304}'), ('Ǧ', 'G', '\u{30C}'), ('ǧ', 'g', '\u{30C}'), ('Ǩ', 'K', '\u{30C}'), ('ǩ', 'k', '\u{30C}'), ('Ǫ', 'O', '\u{328}'),
+    ('ǫ', 'o', '\u{328}'), ('Ǭ', 'Ǫ', '\u{304}'), ('ǭ', 'ǫ', '\u{304}'), ('Ǯ', 'Ʒ', '\u{30C}'), ('ǯ', 'ʒ', '\u{30C}'), ('ǰ', 'j', '\u{30C}'),
+    ('Ǵ', 'G', '\u{301}'), ('ǵ', 'g', '\u{301}'), ('Ǹ', 'N', '\u{300}'), ('ǹ', 'n', '\u{300}'), ('Ǻ', 'Å', '\u{301}'), ('ǻ', 'å', '\u{301}'),
+    ('Ǽ', 'Æ', '\u{301}'), ('ǽ', 'æ', '\u{301}'), ('Ǿ', 'Ø', '\u{301}'), ('ǿ', 'ø', '\u{301}'), ('Ȁ', 'A', '\u{30F}'), ('ȁ', 'a', '\u{30F}'),
+    ('Ȃ', 'A', '\u{311}'), ('ȃ', 'a', '\u{311}'), ('Ȅ', 'E', '\u{30F}'), ('ȅ', 'e', '\u{30F}'), ('Ȇ', 'E', '\u{311}'), ('ȇ', 'e', '\u{311}'),
+    ('Ȉ', 'I', '\u{30F}'), ('ȉ', 'i', '\u{30F}'), ('Ȋ', 'I', '\u{311}'), ('ȋ', 'i', '\u{311}'), ('Ȍ', 'O', '\u{30F}'), ('ȍ', 'o', '\u{30F}'),
+    ('Ȏ', 'O', '\u{311}'), ('ȏ', 'o', '\u{311}'), ('Ȑ', 'R', '\u{30F}'), ('ȑ', 'r', '\u{30F}'), ('Ȓ', 'R', '\u{311}'), ('ȓ', 'r', '\u{311}'),
+    ('Ȕ', 'U', '\u{30F}'), ('ȕ', 'u', '\u{30F}'), ('Ȗ', 'U', '\u{311}'), ('ȗ', 'u', '\u{311}'), ('Ș', 'S', '\u{326}'), ('ș', 's', '\u{326}'),
+    ('Ț', 'T', '\u{326}'), ('ț', 't', '\u{326}'), ('Ȟ', 'H', '\u{30C}'), ('ȟ', 'h', '\u{30C}'), ('Ȧ', 'A', '\u{307}'), ('ȧ', 'a', '\u{307}'),
+    ('Ȩ', 'E', '\u{327}'), ('ȩ', 'e', '\u{327}'), ('Ȫ', 'Ö', '\u{304}'), ('ȫ', 'ö', '\u{304}'), ('Ȭ', 'Õ', '\u{304}'), ('ȭ', 'õ', '\u{304}'),
+    ('Ȯ', 'O', '\u{307}'), ('ȯ', 'o', '\u{307}'), ('Ȱ', 'Ȯ', '\u{304}'), ('ȱ', 'ȯ', '\u{304}'), ('Ȳ', 'Y', '\u{304}'), ('ȳ', 'y', '\u{304}'),
+    ('Ḁ', 'A', '\u{325}'), ('ḁ', 'a', '\u{325}'), ('Ḃ', 'B', '\u{307}'), ('ḃ', 'b', '\u{307}'), ('Ḅ', 'B', '\u{323}'), ('ḅ', 'b', '\u{323}'),
+    ('Ḇ', 'B', '\u{331}'), ('ḇ', 'b', '\u{331}'), ('Ḉ', 'Ç', '\u{301}'), ('ḉ', 'ç', '\u{301}'), ('Ḋ', 'D', '\u{307}'), ('ḋ', 'd', '\u{307}'),
+    ('Ḍ', 'D', '\u{323}'), ('ḍ', 'd', '\u{323}'), ('Ḏ', 'D', '\u{331}'), ('ḏ', 'd', '\u{331}'), ('Ḑ', 'D', '\u{327}'), ('ḑ', 'd', '\u{327}'),
+    ('Ḓ', 'D', '\u{32D}'), ('ḓ', 'd', '\u{32D}'), ('Ḕ', 'Ē', '\u{300}'), ('ḕ', 'ē', '\u{300}'), ('Ḗ', 'Ē', '\u{301}'), ('ḗ', 'ē', '\u{301}'),
+    ('Ḙ', 'E', '\u{32D}'), ('ḙ', 'e', '\u{32D}'), ('Ḛ', 'E', '\u{330}'), ('ḛ', 'e', '\u{330}'), ('Ḝ', 'Ȩ', '\u{306}'), ('ḝ', 'ȩ', '\u{306}'),
+    ('Ḟ', 'F', '\u{307}'), ('ḟ', 'f', '\u{307}'), ('Ḡ', 'G', '\u{304}'), ('ḡ', 'g', '\u{304}'), ('Ḣ', 'H', '\u{307}'), ('ḣ', 'h', '\u{307}'),
+    ('Ḥ', 'H', '\u{323}'), ('ḥ', 'h', '\u{323}'), ('Ḧ', 'H', '\u{308}'), ('ḧ', 'h', '\u{308}'), ('Ḩ', 'H', '\u{327}'), ('ḩ', 'h', '\u{327}'),
+    ('Ḫ', 'H', '\u{32E}'), ('ḫ', 'h', '\u{32E}'), ('Ḭ', 'I', '\u{330}'), ('ḭ', 'i', '\u{330}'), ('Ḯ', 'Ï', '\u{301}'), ('ḯ', 'ï', '\u{301}'),
+    ('Ḱ', 'K', '\u{301}'), ('ḱ', 'k', '\u{301}'), ('Ḳ', 'K', '\u{323}'), ('ḳ', 'k', '\u{323}'), ('Ḵ', 'K', '\u{331}'), ('ḵ', 'k', '\u{331}'),
+    ('Ḷ', 'L', '\u{323}'), ('ḷ', 'l', '\u{323}'), ('Ḹ', 'Ḷ', '\u{304}'), ('ḹ', 'ḷ', '\u{304}'), ('Ḻ', 'L', '\u{331}'), ('ḻ', 'l', '\u{331}'),
+    ('Ḽ', 'L', '\u{32D}'), ('ḽ', 'l', '\u{32D}'), ('Ḿ', 'M', '\u{301}'), ('ḿ', 'm', '\u{301}'), ('Ṁ', 'M', '\u{307}'), ('ṁ', 'm', '\u{307}'),
+    ('Ṃ', 'M', '\u{323}'), ('ṃ', 'm', '\u{323}'), ('Ṅ', 'N', '\u{307}'), ('ṅ', 'n', '\u{307}'), ('Ṇ', 'N', '\u{323}'), ('ṇ', 'n', '\u{323}'),
+    ('Ṉ', 'N', '\u{331}'), ('ṉ', 'n', '\u{331}'), ('Ṋ', 'N', '\u{32D}'), ('ṋ', 'n', '\u{32D}'), ('Ṍ', 'Õ', '\u{301}'), ('ṍ', 'õ', '\u{301}'),
+    ('Ṏ', 'Õ', '\u{308}'), ('ṏ', 'õ', '\u{308}'), ('Ṑ', 'Ō', '\u{300}'), ('ṑ', 'ō', '\u{300}'), ('Ṓ', 'Ō', '\u{301}'), ('ṓ', 'ō', '\u{301}'),
+    ('Ṕ', 'P', '\u{301}'), ('ṕ', 'p', '\u{301}'), ('Ṗ', 'P', '\u{307}'), ('ṗ', 'p', '\u{307}'), ('Ṙ', 'R', '\u{307}'), ('ṙ', 'r', '\u{307}'),
+    ('Ṛ', 'R', '\u{323}'), ('ṛ', 'r', '\u{323}'), ('Ṝ', 'Ṛ', '\u{304}'), ('ṝ', 'ṛ', '\u{304}'), ('Ṟ', 'R', '\u{331}'), ('ṟ', 'r', '\u{331}'),
+    ('Ṡ', 'S', '\u{307}'), ('ṡ', 's', '\u{307}'), ('Ṣ', 'S', '\u{323}'), ('ṣ', 's', '\u{323}'), ('Ṥ', 'Ś', '\u{307}'), ('ṥ', 'ś', '\u{307}'),
+    ('Ṧ', 'Š', '\u{307}'), ('ṧ', 'š', '\u{307}'), ('Ṩ', 'Ṣ', '\u{307}'), ('ṩ', 'ṣ', '\u{307}'), ('Ṫ', 'T', '\u{307}'), ('ṫ', 't', '\u{307}'),
+    ('Ṭ', 'T', '\u{323}'), ('ṭ', 't', '\u{323}'), ('Ṯ', 'T', '\u{331}'), ('ṯ', 't', '\u{331}'), ('Ṱ', 'T', '\u{32D}'), ('ṱ', 't', '\u{32D}'),
+    ('Ṳ', 'U', '\u{324}'), ('ṳ', 'u', '\u{324}'), ('Ṵ', 'U', '\u{330}'), ('ṵ', 'u', '\u{330}'), ('Ṷ', 'U', '\u{32D}'), ('ṷ', 'u', '\u{32D}'),
+    ('Ṹ', 'Ũ', '\u{301}'), ('ṹ', 'ũ', '\u{301}'), ('Ṻ', 'Ū', '\u{308}'), ('ṻ', 'ū', '\u{308}'), ('Ṽ', 'V', '\u{303}'), ('ṽ', 'v', '\u{303}'),
+    ('Ṿ', 'V', '\u{323}'), ('ṿ', 'v', '\u{323}'), ('Ẁ', 'W', '\u{300}'), ('ẁ', 'w', '\u{300}'), ('Ẃ', 'W', '\u{301}'), ('ẃ', 'w', '\u{301}'),
+    ('Ẅ', 'W', '\u{308}'), ('ẅ', 'w', '\u{308}'), ('Ẇ', 'W', '\u{307}'), ('ẇ', 'w', '\u{307}'), ('Ẉ', 'W', '\u{323}'), ('ẉ', 'w', '\u{323}'),
+    ('Ẋ', 'X', '\u{307}'), ('ẋ', 'x', '\u{307}'), ('Ẍ', 'X', '\u{308}'), ('ẍ', 'x', '\u{308}'), ('Ẏ', 'Y', '\u{307}'), ('ẏ', 'y', '\u{307}'),
+    ('Ẑ', 'Z', '\u{302}'), ('ẑ', 'z', '\u{302}'), ('Ẓ', 'Z', '\u{323}'), ('ẓ', 'z', '\u{323}'), ('Ẕ', 'Z', '\u{331}'), ('ẕ', 'z', '\u{331}'),
+    ('ẖ', 'h', '\u{331}'), ('ẗ', 't', '\u{308}'), ('ẘ', 'w', '\u{30A}'), ('ẙ', 'y', '\u{30A}'), ('ẛ', 'ſ', '\u{307}'), ('Ạ', 'A', '\u{323}'),
+    ('ạ', 'a', '\u{323}'), ('Ả', 'A', '\u{309}'), ('ả', 'a', '\u{309}'), ('Ấ', 'Â', '\u{301}'), ('ấ', 'â', '\u{301}'), ('Ầ', 'Â', '\u{300}'),
+    ('ầ', 'â', '\u{300}'), ('Ẩ', 'Â', '\u{309}'), ('ẩ', 'â', '\u{309}'), ('Ẫ', 'Â', '\u{303}'), ('ẫ', 'â', '\u{303}'), ('Ậ', 'Ạ', '\u{302}'),
+    ('ậ', 'ạ', '\u{302}'), ('Ắ', 'Ă', '\u{301}'), ('ắ', 'ă', '\u{301}'), ('Ằ', 'Ă', '\u{300}'), ('ằ', 'ă', '\u{300}'), ('Ẳ', 'Ă', '\u{309}'),
+    ('ẳ', 'ă', '\u{309}'), ('Ẵ', 'Ă', '\u{303}'), ('ẵ', 'ă', '\u{303}'), ('Ặ', 'Ạ', '\u{306}'), ('ặ', 'ạ', '\u{306}'), ('Ẹ', 'E', '\u{323}'),
+    ('ẹ', 'e', '\u{323}'), ('Ẻ', 'E', '\u{309}'), ('ẻ', 'e', '\u{309}'), ('Ẽ', 'E', '\u{303}'), ('ẽ', 'e', '\u{303}'), ('Ế', 'Ê', '\u{301}'),
+    ('ế', 'ê', '\u{301}'), ('Ề', 'Ê', '\u{300}'), ('ề', 'ê', '\u{300}'), ('Ể', 'Ê', '\u{309}'), ('ể', 'ê', '\u{309}'), ('Ễ', 'Ê', '\u{303}'),
+    ('ễ', 'ê', '\u{303}'), ('Ệ', 'Ẹ', '\u{302}'), ('ệ', 'ẹ', '\u{302}'), ('Ỉ', 'I', '\u{309}'), ('ỉ', 'i', '\u{309}'), ('Ị', 'I', '\u{323}'),
+    ('ị', 'i', '\u{323}'), ('Ọ', 'O', '\u{323}'), ('ọ', 'o', '\u{323}'), ('Ỏ', 'O', '\u{309}'), ('ỏ', 'o', '\u{309}'), ('Ố', 'Ô', '\u{301}'),
+    ('ố', 'ô', '\u{301}'), ('Ồ', 'Ô', '\u{300}'), ('ồ', 'ô', '\u{300}'), ('Ổ', 'Ô', '\u{309}'), ('ổ', 'ô', '\u{309}'), ('Ỗ', 'Ô', '\u{303}'),
+    ('ỗ', 'ô', '\u{303}'), ('Ộ', 'Ọ', '\u{302}'), ('ộ', 'ọ', '\u{302}'), ('Ớ', 'Ơ', '\u{301}'), ('ớ', 'ơ', '\u{301}'), ('Ờ', 'Ơ', '\u{300}'),
+    ('ờ', 'ơ', '\u{300}'), ('Ở', 'Ơ', '\u{309}'), ('ở', 'ơ', '\u{309}'), ('Ỡ', 'Ơ', '\u{303}'), ('ỡ', 'ơ', '\u{303}'), ('Ợ', 'Ơ', '\u{323}'),
+    ('ợ', 'ơ', '\u{323}'), ('Ụ', 'U', '\u{323}'), ('ụ', 'u', '\u{323}'), ('Ủ', 'U', '\u{309}'), ('ủ', 'u', '\u{309}'), ('Ứ', 'Ư', '\u{301}'),
+    ('ứ', 'ư', '\u{301}'), ('Ừ', 'Ư', '\u{300}'), ('ừ', 'ư', '\u{300}'), ('Ử', 'Ư', '\u{309}'), ('ử', 'ư', '\u{309}'), ('Ữ', 'Ư', '\u{303}'),
+    ('ữ', 'ư', '\u{303}'), ('Ự', 'Ư', '\u{323}'), ('ự', 'ư', '\u{323}'), ('Ỳ', 'Y', '\u{300}'), ('ỳ', 'y', '\u{300}'), ('Ỵ', 'Y', '\u{323}'),
+    ('ỵ', 'y', '\u{323}'), ('Ỷ', 'Y', '\u{309}'), ('ỷ', 'y', '\u{309}'), ('Ỹ', 'Y', '\u{303}'), ('ỹ', 'y', '\u{303}'),
+];
+
+/// `COMPOSITIONS` by precomposed character
+fn decompositions() -> &'static HashMap<char, (char, char)> {
+    static DECOMPOSITIONS: OnceLock<HashMap<char, (char, char)>> = OnceLock::new();
+    DECOMPOSITIONS.get_or_init(|| COMPOSITIONS.iter().map(|&(composed, base, mark)| (composed, (base, mark))).collect())
+}
+
+/// `COMPOSITIONS` by base and mark
+fn compositions() -> &'static HashMap<(char, char), char> {
+    static COMPOSED: OnceLock<HashMap<(char, char), char>> = OnceLock::new();
+    COMPOSED.get_or_init(|| COMPOSITIONS.iter().map(|&(composed, base, mark)| ((base, mark), composed)).collect())
+}
+
+/// Decomposes recursively, so `ễ` becomes `e` + circumflex + tilde
+fn nfd(text: &str) -> String {
+    let mut decomposed: String = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_decomposed(c, &mut decomposed);
+    }
+    decomposed
+}
+
+fn push_decomposed(c: char, out: &mut String) {
+    match decompositions().get(&c) {
+        Some(&(base, mark)) => {
+            push_decomposed(base, out);
+            out.push(mark);
+        }
+        None => out.push(c),
+    }
+}
+
+/// Composes each base with the marks following it wherever a precomposed character exists
+pub fn nfc(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_owned();
+    }
+    let mut composed: String = String::with_capacity(text.len());
+    for c in nfd(text).chars() {
+        let last: Option<char> = composed.chars().next_back();
+        match last.and_then(|last| compositions().get(&(last, c))) {
+            Some(&precomposed) => {
+                composed.pop();
+                composed.push(precomposed);
+            }
+            None => composed.push(c),
+        }
+    }
+    composed
+}
+
+/// Lowercased with the combining marks dropped
+fn sort_key(name: &str) -> String {
+    if name.is_ascii() {
+        return name.to_ascii_lowercase();
+    }
+    nfd(name).chars()
+        .filter(|c| !is_combining(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn is_combining(c: char) -> bool {
+    matches!(c as u32, 0x0300..=0x036F | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF | 0xFE20..=0xFE2F)
+}
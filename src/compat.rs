@@ -0,0 +1,139 @@
+//! `--compat`, output shaped exactly like GNU `tree -J` and `tree -X` so scripts parsing those keep working, and `--gnu`
+//! for GNU tree's short flags.
+//!
+//! Like `tree` run without arguments the root is `.`, and the report at the end counts everything but the root.
+
+use crate::entry::{self, Entry};
+use crate::format::{html_escape, json_string, Format};
+use crate::style;
+use clap::ValueEnum;
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compat {
+    /// `tree -J`
+    TreeJson,
+    /// `tree -X`
+    TreeXml,
+}
+
+impl From<Compat> for Format {
+    fn from(compat: Compat) -> Format {
+        match compat {
+            Compat::TreeJson => Format::TreeJson,
+            Compat::TreeXml => Format::TreeXml,
+        }
+    }
+}
+
+/// With `--gnu`, the short flags that mean something else here rewritten to what they mean in `tree`: `-L` to
+/// `--depth`, `-d` to `--dirs-only` and `-l` to `--follow-symlinks`. Everything after `--` is left alone
+pub fn gnu_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let args: Vec<OsString> = args.into_iter().collect();
+    let end: usize = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+    if !args[..end].iter().any(|arg| arg == "--gnu") {
+        return args;
+    }
+    args.into_iter().enumerate().map(|(i, arg)| match arg.to_str() {
+        _ if i >= end => arg,
+        Some("-L") => OsString::from("--depth"),
+        //`-L2`
+        Some(flag) if flag.starts_with("-L") => OsString::from(format!("--depth={}", &flag[2..])),
+        Some("-d") => OsString::from("--dirs-only"),
+        Some("-l") => OsString::from("--follow-symlinks"),
+        _ => arg,
+    }).collect()
+}
+
+/// What `tree` calls an entry, and where it points for links
+fn kind(entry: &Entry) -> (&'static str, Option<PathBuf>) {
+    let path: &Path = Path::new(&entry.path);
+    match fs::read_link(path) {
+        Ok(target) => ("link", Some(target)),
+        Err(_) if entry.is_dir => ("directory", None),
+        Err(_) => ("file", None),
+    }
+}
+
+/// `[{"type":"directory","name":".","contents":[...]}, {"type":"report",...}]`, one entry per line
+pub fn json(entries: &[Entry]) -> String {
+    let mut lines: Vec<String> = vec![String::from("["), String::from("  {\"type\":\"directory\",\"name\":\".\",\"contents\":[")];
+    json_contents(entries, 2, &mut lines);
+    let (files, dirs) = listed_kinds(entries);
+    lines.push(String::from("  ]}"));
+    lines.push(String::from(","));
+    lines.push(format!("  {{\"type\":\"report\",\"directories\":{dirs},\"files\":{files}}}"));
+    lines.push(String::from("]"));
+    lines.join("\n")
+}
+
+fn json_contents(entries: &[Entry], depth: usize, lines: &mut Vec<String>) {
+    let indent: String = "  ".repeat(depth);
+    for (i, entry) in entries.iter().enumerate() {
+        let comma: &str = if i + 1 < entries.len() { "," } else { "" };
+        let (kind, target) = kind(entry);
+        let mut object: String = format!("{indent}{{\"type\":\"{kind}\",\"name\":{}", json_string(&entry.name));
+        //`tree -J` never lists contents under a link, even a followed one
+        if let Some(target) = target {
+            object.push_str(&format!(",\"target\":{}", json_string(&target.to_string_lossy())));
+            lines.push(format!("{object}}}{comma}"));
+            continue;
+        }
+        if !entry.is_dir {
+            lines.push(format!("{object}}}{comma}"));
+            continue;
+        }
+        lines.push(format!("{object},\"contents\":["));
+        json_contents(&entry.children, depth + 1, lines);
+        lines.push(format!("{indent}]}}{comma}"));
+    }
+}
+
+/// (files, directories) the JSON lists, which stops at links
+fn listed_kinds(entries: &[Entry]) -> (usize, usize) {
+    entries.iter().fold((0, 0), |(files, dirs), entry| {
+        let (sub_files, sub_dirs) = if kind(entry).1.is_some() { (0, 0) } else { listed_kinds(&entry.children) };
+        if entry.is_dir { (files + sub_files, dirs + sub_dirs + 1) } else { (files + sub_files + 1, dirs + sub_dirs) }
+    })
+}
+
+/// `<tree><directory name=".">...</directory><report>...</report></tree>`, one element per line. Control
+/// characters in names are escaped unless `literal`, XML can't hold most of them anyway
+pub fn xml(entries: &[Entry], literal: bool) -> String {
+    let mut lines: Vec<String> = vec![
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"),
+        String::from("<tree>"),
+        String::from("  <directory name=\".\">"),
+    ];
+    xml_contents(entries, 2, literal, &mut lines);
+    let (files, dirs) = entry::count_kinds(entries);
+    lines.push(String::from("  </directory>"));
+    lines.push(String::from("  <report>"));
+    lines.push(format!("    <directories>{dirs}</directories>"));
+    lines.push(format!("    <files>{files}</files>"));
+    lines.push(String::from("  </report>"));
+    lines.push(String::from("</tree>"));
+    lines.join("\n")
+}
+
+fn xml_contents(entries: &[Entry], depth: usize, literal: bool, lines: &mut Vec<String>) {
+    let attribute = |text: &str| html_escape(&if literal { Cow::Borrowed(text) } else { style::escape_control(text) });
+    let indent: String = "  ".repeat(depth);
+    for entry in entries {
+        let (kind, target) = kind(entry);
+        let mut open: String = format!("{indent}<{kind} name=\"{}\"", attribute(&entry.name));
+        if let Some(target) = target {
+            open.push_str(&format!(" target=\"{}\"", attribute(&target.to_string_lossy())));
+        }
+        if !entry.is_dir || entry.children.is_empty() {
+            lines.push(format!("{open}></{kind}>"));
+            continue;
+        }
+        lines.push(format!("{open}>"));
+        xml_contents(&entry.children, depth + 1, literal, lines);
+        lines.push(format!("{indent}</{kind}>"));
+    }
+}
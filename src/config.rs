@@ -0,0 +1,292 @@
+//! `.project-tree.toml` in the project root, falling back to `~/.config/project-tree/config.toml`.
+//!
+//! TOML basic strings share JSON's escapes, so values are written with the JSON quoting.
+
+use crate::class::Class;
+use crate::format::json_string;
+use crate::glob;
+use crate::style::Style;
+use crate::toml::{self, Table, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const FILE_NAME: &str = ".project-tree.toml";
+
+/// Entries without a `/` are names matched at any depth, the others are paths from the root
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Added to the default ignore list
+    pub ignore: Vec<String>,
+    /// Added to the default stop list
+    pub stop: Vec<String>,
+    /// Replaces the built-in `.git`, `.vscode`
+    pub default_ignore: Option<Vec<String>>,
+    /// Replaces the built-in `node_modules` and detected build directories
+    pub default_stop: Option<Vec<String>>,
+    /// Globs of entries shown dimmed, added to `--dim`
+    pub dim: Vec<String>,
+    /// After directory names instead of `/`, `--dir-suffix` wins
+    pub dir_suffix: Option<String>,
+    /// `[colors]`, globs and the style for the entries matching them, the least specific first
+    pub colors: Vec<(String, Style)>,
+    /// `[rules."vendor/"]` sections, the least specific first
+    pub rules: Vec<DirRule>,
+    /// `[theme]`, or the `--theme` file
+    pub theme: Option<Theme>,
+}
+
+/// Styles by what an entry is, replacing the type colors (LS_COLORS or the built-in palette). `[colors]` still wins
+#[derive(Clone, Debug, Default)]
+pub struct Theme {
+    pub dir: Option<Style>,
+    pub file: Option<Style>,
+    /// Falls back to `file`
+    pub executable: Option<Style>,
+    pub symlink: Option<Style>,
+    /// Falls back to `symlink`
+    pub broken_symlink: Option<Style>,
+    /// FIFOs, sockets and devices, falls back to `file`
+    pub special: Option<Style>,
+    /// Added over the others for what the root `.gitignore` matches
+    pub gitignored: Option<Style>,
+}
+
+impl Theme {
+    pub fn style(&self, class: Class, gitignored: bool) -> Option<Style> {
+        let style: Option<Style> = match class {
+            Class::Dir => self.dir,
+            Class::File => self.file,
+            Class::Executable => self.executable.or(self.file),
+            Class::Link => self.symlink,
+            Class::BrokenLink => self.broken_symlink.or(self.symlink),
+            Class::Fifo | Class::Socket | Class::BlockDevice | Class::CharDevice => self.special.or(self.file),
+        };
+        match (style, self.gitignored.filter(|_| gitignored)) {
+            (Some(style), Some(ignored)) => Some(style.merge(ignored)),
+            (style, ignored) => ignored.or(style),
+        }
+    }
+
+    fn from_table(table: &Table) -> Result<Theme, String> {
+        let mut theme: Theme = Theme::default();
+        for (key, value) in table {
+            let slot: &mut Option<Style> = match key.as_str() {
+                "dir" => &mut theme.dir,
+                "file" => &mut theme.file,
+                "executable" => &mut theme.executable,
+                "symlink" => &mut theme.symlink,
+                "broken_symlink" => &mut theme.broken_symlink,
+                "special" => &mut theme.special,
+                "gitignored" => &mut theme.gitignored,
+                _ => return Err(format!("unknown class `{key}`")),
+            };
+            let spec: &str = value.as_str().ok_or_else(|| format!("`{key}` must be a string"))?;
+            *slot = Some(Style::parse(spec).map_err(|message| format!("`{key}`: {message}"))?);
+        }
+        Ok(theme)
+    }
+
+    /// A theme file for `--theme`, with a `[theme]` section like the config or the classes at the top level
+    pub fn load(path: &Path) -> io::Result<Theme> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {message}", path.display()));
+        let table: Table = toml::parse(&fs::read_to_string(path)?).map_err(invalid)?;
+        match table.get("theme") {
+            Some(value) => Theme::from_table(value.as_table().ok_or_else(|| invalid(String::from("`theme` must be a table")))?),
+            None => Theme::from_table(&table),
+        }.map_err(invalid)
+    }
+}
+
+/// Settings for one part of the tree, applying to the directory and everything below it
+#[derive(Clone, Debug, Default)]
+pub struct DirRule {
+    /// Path from the root, or a name matched at any depth when it has no `/`
+    pub pattern: String,
+    /// Levels shown below the directory, like `--depth-for`
+    pub depth: Option<usize>,
+    /// Show everything in the dimmed style
+    pub dim: Option<bool>,
+    /// Name globs to leave out below the directory
+    pub ignore: Vec<String>,
+    /// Name globs to not recurse into below the directory
+    pub stop: Vec<String>,
+    /// Put directories before files, like `--dirs`
+    pub dirs_first: Option<bool>,
+}
+
+impl DirRule {
+    /// Whether `path` (relative, `/` separated) is the directory or below it
+    pub fn covers(&self, path: &str) -> bool {
+        if self.pattern.contains('/') {
+            path == self.pattern || path.starts_with(&format!("{}/", self.pattern))
+        } else {
+            path.split('/').any(|segment| segment == self.pattern)
+        }
+    }
+
+    pub fn ignores(&self, name: &str) -> bool {
+        self.ignore.iter().any(|pattern| glob::matches(pattern, name))
+    }
+
+    pub fn stops(&self, name: &str) -> bool {
+        self.stop.iter().any(|pattern| glob::matches(pattern, name))
+    }
+}
+
+impl Config {
+    /// Loads the project config, or the user config if the project has none, or the defaults if neither exists
+    pub fn load() -> io::Result<Config> {
+        let candidates: [Option<PathBuf>; 2] = [Some(PathBuf::from(FILE_NAME)), user_config_path()];
+        match candidates.into_iter().flatten().find(|path| path.is_file()) {
+            Some(path) => Config::from_file(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    fn from_file(path: &Path) -> io::Result<Config> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {message}", path.display()));
+        let table: Table = toml::parse(&fs::read_to_string(path)?).map_err(invalid)?;
+
+        let list = |key: &str| -> io::Result<Option<Vec<String>>> {
+            match table.get(key) {
+                None => Ok(None),
+                Some(value) => strings(value).map(Some).ok_or_else(|| invalid(format!("`{key}` must be an array of strings"))),
+            }
+        };
+        let mut rules: Vec<DirRule> = Vec::new();
+        if let Some(value) = table.get("rules") {
+            let sections: &Table = value.as_table().ok_or_else(|| invalid(String::from("`rules` must be a table of directories")))?;
+            for (pattern, section) in sections {
+                let section: &Table = section.as_table().ok_or_else(|| invalid(format!("`rules.\"{pattern}\"` must be a table")))?;
+                rules.push(DirRule::from_table(pattern, section).map_err(|message| invalid(format!("`rules.\"{pattern}\"`: {message}")))?);
+            }
+        }
+        let mut colors: Vec<(String, Style)> = Vec::new();
+        if let Some(value) = table.get("colors") {
+            let section: &Table = value.as_table().ok_or_else(|| invalid(String::from("`colors` must be a table of globs")))?;
+            for (pattern, spec) in section {
+                let spec: &str = spec.as_str().ok_or_else(|| invalid(format!("`colors.\"{pattern}\"` must be a string")))?;
+                let style: Style = Style::parse(spec).map_err(|message| invalid(format!("`colors.\"{pattern}\"`: {message}")))?;
+                colors.push((pattern.trim_start_matches("./").to_owned(), style));
+            }
+        }
+        //Longer patterns are taken as more specific, and win when several match
+        colors.sort_by_key(|(pattern, _)| pattern.len());
+
+        //Deeper paths are more specific, their settings win over the ones of the directories around them
+        rules.sort_by_key(|rule| rule.pattern.matches('/').count());
+
+        Ok(Config {
+            ignore: list("ignore")?.unwrap_or_default(),
+            stop: list("stop")?.unwrap_or_default(),
+            default_ignore: list("default_ignore")?,
+            default_stop: list("default_stop")?,
+            dim: list("dim")?.unwrap_or_default(),
+            dir_suffix: match table.get("dir_suffix") {
+                None => None,
+                Some(value) => Some(value.as_str().ok_or_else(|| invalid(String::from("`dir_suffix` must be a string")))?.to_owned()),
+            },
+            colors,
+            rules,
+            theme: match table.get("theme") {
+                None => None,
+                Some(value) => {
+                    let section: &Table = value.as_table().ok_or_else(|| invalid(String::from("`theme` must be a table")))?;
+                    Some(Theme::from_table(section).map_err(|message| invalid(format!("`theme`: {message}")))?)
+                }
+            },
+        })
+    }
+}
+
+impl DirRule {
+    fn from_table(pattern: &str, table: &Table) -> Result<DirRule, String> {
+        let mut rule = DirRule {
+            pattern: pattern.trim_start_matches("./").trim_end_matches('/').to_owned(),
+            ..DirRule::default()
+        };
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("depth", Value::Integer(levels)) if *levels >= 0 => rule.depth = Some(*levels as usize),
+                ("dim", Value::Boolean(dim)) => rule.dim = Some(*dim),
+                ("dirs_first", Value::Boolean(dirs_first)) => rule.dirs_first = Some(*dirs_first),
+                ("ignore", value) => rule.ignore = strings(value).ok_or("`ignore` must be an array of strings")?,
+                ("stop", value) => rule.stop = strings(value).ok_or("`stop` must be an array of strings")?,
+                ("depth" | "dim" | "dirs_first", _) => return Err(format!("invalid `{key}`")),
+                _ => return Err(format!("unknown setting `{key}`")),
+            }
+        }
+        Ok(rule)
+    }
+}
+
+/// Adds paths to the `ignore` and `stop` lists of the project config, creating it if needed.
+/// Only those two lines are rewritten, so comments and the rest of the file are left as they were
+pub fn add_entries(ignore: &[String], stop: &[String]) -> io::Result<()> {
+    let path: &Path = Path::new(FILE_NAME);
+    let mut text: String = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let table: Table = toml::parse(&text).map_err(|message| io::Error::new(io::ErrorKind::InvalidData, format!("{FILE_NAME}: {message}")))?;
+
+    for (key, additions) in [("ignore", ignore), ("stop", stop)] {
+        if additions.is_empty() { continue; }
+        let mut list: Vec<String> = table.get(key).and_then(strings).unwrap_or_default();
+        for addition in additions {
+            if !list.contains(addition) { list.push(addition.clone()); }
+        }
+        let quoted: Vec<String> = list.iter().map(|entry| json_string(entry)).collect();
+        text = set_top_level(&text, key, &format!("{key} = [{}]", quoted.join(", ")));
+    }
+    fs::write(path, text)
+}
+
+/// Replaces the `key = ...` line (and the rest of a multi-line array) before the first table, or adds one there
+fn set_top_level(text: &str, key: &str, replacement: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let top_level_end: usize = lines.iter().position(|line| line.trim_start().starts_with('[')).unwrap_or(lines.len());
+    let is_key = |line: &str| line.trim_start().strip_prefix(key).is_some_and(|rest| rest.trim_start().starts_with('='));
+
+    let mut result: Vec<&str> = Vec::with_capacity(lines.len() + 1);
+    match lines[..top_level_end].iter().position(|line| is_key(line)) {
+        Some(start) => {
+            //An array can go on over several lines, up to its closing bracket
+            let mut end: usize = start;
+            let mut depth: isize = 0;
+            for (i, line) in lines.iter().enumerate().skip(start) {
+                depth += line.matches('[').count() as isize - line.matches(']').count() as isize;
+                end = i;
+                if depth <= 0 { break; }
+            }
+            result.extend(&lines[..start]);
+            result.push(replacement);
+            result.extend(&lines[end + 1..]);
+        }
+        None => {
+            //After the last top level line, not after the blank lines separating it from the first table
+            let mut at: usize = top_level_end;
+            while at > 0 && lines[at - 1].trim().is_empty() { at -= 1; }
+            result.extend(&lines[..at]);
+            result.push(replacement);
+            result.extend(&lines[at..]);
+        }
+    }
+    let mut text: String = result.join("\n");
+    text.push('\n');
+    text
+}
+
+fn strings(value: &Value) -> Option<Vec<String>> {
+    value.as_array()?.iter().map(|item| item.as_str().map(str::to_owned)).collect()
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    let config_home: PathBuf = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?).join(".config"),
+    };
+    Some(config_home.join("project-tree").join("config.toml"))
+}
@@ -0,0 +1,145 @@
+//! Features that look inside files. Everything here goes through `read_text`/`fs::read` on paths
+//! that passed `is_file()`, so FIFOs and devices are never opened.
+
+use std::fs;
+use std::ops::AddAssign;
+use std::path::Path;
+
+/// Searches the raw bytes, so the pattern is found in files that aren't valid UTF-8 too
+pub fn file_contains(path: &Path, pattern: &str) -> bool {
+    let Ok(contents) = fs::read(path) else { return false };
+    pattern.is_empty() || contents.windows(pattern.len()).any(|window| window == pattern.as_bytes())
+}
+
+/// Reads `path` if it looks like text (no NUL bytes near the start, like git's heuristic)
+pub fn read_text(path: &Path) -> Option<String> {
+    let bytes: Vec<u8> = fs::read(path).ok()?;
+    if bytes.iter().take(8000).any(|&byte| byte == 0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+const TODO_MARKERS: [&str; 3] = ["TODO", "FIXME", "HACK"];
+
+/// Number of TODO, FIXME and HACK markers, in that order
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TodoCounts([usize; 3]);
+
+impl TodoCounts {
+    pub fn of_file(path: &Path) -> TodoCounts {
+        let Some(text) = read_text(path) else { return TodoCounts::default() };
+
+        let mut counts: TodoCounts = TodoCounts::default();
+        for (count, marker) in counts.0.iter_mut().zip(TODO_MARKERS) {
+            *count = text.match_indices(marker).filter(|&(i, _)| is_whole_word(&text, i, marker.len())).count();
+        }
+        counts
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&count| count == 0)
+    }
+}
+
+impl AddAssign for TodoCounts {
+    fn add_assign(&mut self, other: TodoCounts) {
+        for (count, other) in self.0.iter_mut().zip(other.0) {
+            *count += other;
+        }
+    }
+}
+
+/// Formats as `[TODO 2, FIXME 1]`, skipping markers that weren't found
+impl std::fmt::Display for TodoCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let parts: Vec<String> = TODO_MARKERS.iter().zip(self.0)
+            .filter(|&(_, count)| count > 0)
+            .map(|(marker, count)| format!("{marker} {count}"))
+            .collect();
+        write!(f, "[{}]", parts.join(", "))
+    }
+}
+
+/// So `TODOS` or `MY_HACK_FLAG` don't count
+fn is_whole_word(text: &str, start: usize, len: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let before: Option<char> = text[..start].chars().next_back();
+    let after: Option<char> = text[start + len..].chars().next();
+    !before.is_some_and(is_word) && !after.is_some_and(is_word)
+}
+
+/// Magic numbers at the start of a file, checked in order
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "png image"),
+    (b"\xff\xd8\xff", "jpeg image"),
+    (b"GIF87a", "gif image"),
+    (b"GIF89a", "gif image"),
+    (b"BM", "bmp image"),
+    (b"\x00\x00\x01\x00", "ico image"),
+    (b"II*\x00", "tiff image"),
+    (b"MM\x00*", "tiff image"),
+    (b"%PDF-", "pdf document"),
+    (b"PK\x03\x04", "zip archive"),
+    (b"\x1f\x8b", "gzip archive"),
+    (b"BZh", "bzip2 archive"),
+    (b"\xfd7zXZ\x00", "xz archive"),
+    (b"\x28\xb5\x2f\xfd", "zstd archive"),
+    (b"7z\xbc\xaf\x27\x1c", "7z archive"),
+    (b"Rar!\x1a\x07", "rar archive"),
+    (b"\x7fELF", "ELF binary"),
+    (b"\xcf\xfa\xed\xfe", "Mach-O binary"),
+    (b"\xce\xfa\xed\xfe", "Mach-O binary"),
+    (b"\xca\xfe\xba\xbe", "Mach-O universal binary / java class"),
+    (b"MZ", "PE/Windows binary"),
+    (b"\x00asm", "wasm binary"),
+    (b"SQLite format 3\x00", "sqlite database"),
+    (b"ID3", "mp3 audio"),
+    (b"OggS", "ogg audio"),
+    (b"fLaC", "flac audio"),
+    (b"wOFF", "woff font"),
+    (b"wOF2", "woff2 font"),
+    (b"\x00\x01\x00\x00\x00", "truetype font"),
+    (b"OTTO", "opentype font"),
+];
+
+/// Detected type of a regular file from its first bytes, `file(1)` style
+pub fn sniff_kind(path: &Path) -> Option<&'static str> {
+    use std::io::Read;
+
+    let mut head: Vec<u8> = Vec::with_capacity(8192);
+    fs::File::open(path).ok()?.take(8192).read_to_end(&mut head).ok()?;
+
+    if head.is_empty() {
+        return Some("empty");
+    }
+    if let Some((_, kind)) = SIGNATURES.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return Some(kind);
+    }
+    //Container formats with the interesting part after a common header
+    if head.starts_with(b"RIFF") && head.len() >= 12 {
+        return Some(match &head[8..12] {
+            b"WEBP" => "webp image",
+            b"WAVE" => "wav audio",
+            b"AVI " => "avi video",
+            _ => "riff data",
+        });
+    }
+    if head.len() >= 12 && &head[4..8] == b"ftyp" {
+        return Some(if &head[8..12] == b"qt  " { "quicktime video" } else { "mp4 video" });
+    }
+    if head.len() >= 262 && &head[257..262] == b"ustar" {
+        return Some("tar archive");
+    }
+
+    if head.contains(&0) {
+        return Some("data");
+    }
+    match std::str::from_utf8(&head) {
+        Ok(text) if text.is_ascii() => Some("ASCII text"),
+        Ok(_) => Some("UTF-8 text"),
+        //Cut off in the middle of a character by the 8K limit is still text
+        Err(err) if err.error_len().is_none() => Some("UTF-8 text"),
+        Err(_) => Some("data"),
+    }
+}
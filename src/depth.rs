@@ -0,0 +1,46 @@
+//! How many levels get shown, overall with `--depth` and below certain directories with `--depth-for`.
+
+/// An override like `node_modules=1`, names without a `/` match at any depth like the config entries
+#[derive(Clone, Debug)]
+pub struct Override {
+    pub pattern: String,
+    pub levels: usize,
+}
+
+/// Parses `PATH=LEVELS`
+pub fn parse_override(value: &str) -> Result<Override, String> {
+    let (pattern, levels) = value.rsplit_once('=').ok_or_else(|| String::from("expected PATH=LEVELS"))?;
+    let pattern: &str = pattern.trim_start_matches("./").trim_end_matches('/');
+    if pattern.is_empty() {
+        return Err(String::from("missing path before the ="));
+    }
+    let levels: usize = levels.parse().map_err(|_| format!("invalid number of levels: {levels}"))?;
+    Ok(Override { pattern: pattern.to_owned(), levels })
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    /// Levels below the root, `None` for no limit
+    pub depth: Option<usize>,
+    pub overrides: Vec<Override>,
+}
+
+impl Limits {
+    /// Levels to show below the root
+    pub fn root(&self) -> Option<usize> {
+        self.depth
+    }
+
+    /// Levels to show below the directory at `path` (relative, `/` separated) inside one with `parent` levels left.
+    /// A matching override replaces the inherited limit, so it can both cut a subtree short and expand it past `--depth`
+    pub fn below(&self, path: &str, name: &str, parent: Option<usize>) -> Option<usize> {
+        //The last matching override wins, so later flags refine earlier ones
+        let matching = self.overrides.iter().rev().find(|rule| {
+            if rule.pattern.contains('/') { rule.pattern == path } else { rule.pattern == name }
+        });
+        match matching {
+            Some(rule) => Some(rule.levels),
+            None => parent.map(|levels| levels.saturating_sub(1)),
+        }
+    }
+}
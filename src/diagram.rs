@@ -0,0 +1,115 @@
+//! The tree as a definition for diagram tools, to render with their own layout and styling. Labels are names, or
+//! paths from the root with `full_path`.
+
+use crate::entry::Entry;
+use crate::style;
+
+/// Graphviz, directories as folders and files as notes, left to right so wide directories don't sprawl
+pub fn dot(entries: &[Entry], root: Option<&str>, full_path: bool) -> String {
+    let mut lines: Vec<String> = vec![
+        String::from("digraph tree {"),
+        String::from("  rankdir=LR;"),
+        String::from("  node [fontname=\"monospace\", fontsize=10];"),
+        String::from("  edge [arrowhead=none];"),
+    ];
+    let root_id: String = dot_string(".");
+    lines.push(format!("  {root_id} [label={}, shape=folder];", dot_string(&format!("{}/", root.unwrap_or(".")))));
+    dot_level(entries, &root_id, full_path, &mut lines);
+    lines.push(String::from("}"));
+    lines.join("\n")
+}
+
+fn dot_level(entries: &[Entry], parent: &str, full_path: bool, lines: &mut Vec<String>) {
+    for entry in entries {
+        //Paths are unique, names aren't
+        let id: String = dot_string(&entry.path);
+        let shape: &str = if entry.is_dir { "folder" } else { "note" };
+        lines.push(format!("  {id} [label={}, shape={shape}];", dot_string(&entry.display_name(full_path))));
+        lines.push(format!("  {parent} -> {id};"));
+        dot_level(&entry.children, &id, full_path, lines);
+    }
+}
+
+fn dot_string(text: &str) -> String {
+    format!("\"{}\"", style::escape_control(text).replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Mermaid flowchart, which GitHub, GitLab and Notion render in a ```` ```mermaid ```` block. Directories are
+/// rectangles and files rounded
+pub fn mermaid(entries: &[Entry], root: Option<&str>, full_path: bool) -> String {
+    let mut lines: Vec<String> = vec![String::from("flowchart LR")];
+    lines.push(format!("  n0[{}]", mermaid_string(&format!("{}/", root.unwrap_or(".")))));
+    mermaid_level(entries, "n0", full_path, &mut 0, &mut lines);
+    lines.join("\n")
+}
+
+fn mermaid_level(entries: &[Entry], parent: &str, full_path: bool, next: &mut usize, lines: &mut Vec<String>) {
+    for entry in entries {
+        //Mermaid ids can't hold most of what's in a path, so they're numbered
+        *next += 1;
+        let id: String = format!("n{next}");
+        let label: String = mermaid_string(&entry.display_name(full_path));
+        let node: String = if entry.is_dir { format!("{id}[{label}]") } else { format!("{id}({label})") };
+        lines.push(format!("  {parent} --- {node}"));
+        mermaid_level(&entry.children, &id, full_path, next, lines);
+    }
+}
+
+/// Quoted so brackets in names aren't read as shapes, quotes themselves as an entity
+fn mermaid_string(text: &str) -> String {
+    format!("\"{}\"", style::escape_control(text).replace('"', "#quot;"))
+}
+
+/// PlantUML Salt tree widget, which draws like a file browser's folder view
+pub fn plantuml(entries: &[Entry], root: Option<&str>, full_path: bool) -> String {
+    let mut lines: Vec<String> = vec![String::from("@startsalt"), String::from("{"), String::from("{T")];
+    lines.push(format!("+ {}", plantuml_string(&format!("{}/", root.unwrap_or(".")))));
+    plantuml_level(entries, 2, full_path, &mut lines);
+    lines.extend(["}", "}", "@endsalt"].map(String::from));
+    lines.join("\n")
+}
+
+fn plantuml_level(entries: &[Entry], depth: usize, full_path: bool, lines: &mut Vec<String>) {
+    for entry in entries {
+        lines.push(format!("{} {}", "+".repeat(depth), plantuml_string(&entry.display_name(full_path))));
+        plantuml_level(&entry.children, depth + 1, full_path, lines);
+    }
+}
+
+/// `|` would start a column, `~` escapes it for creole
+fn plantuml_string(text: &str) -> String {
+    style::escape_control(text).replace('~', "~~").replace('|', "~|")
+}
+
+/// `\dirtree` from the dirtree package, for a project structure figure in a LaTeX document
+pub fn latex(entries: &[Entry], root: Option<&str>, full_path: bool) -> String {
+    let mut lines: Vec<String> = vec![String::from("% \\usepackage{dirtree}"), String::from("\\dirtree{%")];
+    lines.push(format!(".1 {}.", latex_string(&format!("{}/", root.unwrap_or(".")))));
+    latex_level(entries, 2, full_path, &mut lines);
+    lines.push(String::from("}"));
+    lines.join("\n")
+}
+
+fn latex_level(entries: &[Entry], depth: usize, full_path: bool, lines: &mut Vec<String>) {
+    for entry in entries {
+        lines.push(format!(".{depth} {}.", latex_string(&entry.display_name(full_path))));
+        latex_level(&entry.children, depth + 1, full_path, lines);
+    }
+}
+
+fn latex_string(text: &str) -> String {
+    let mut escaped: String = String::with_capacity(text.len());
+    for c in style::escape_control(text).chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,136 @@
+//! Minimal line based unified diff for `--check`.
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Same,
+    Removed,
+    Added,
+}
+
+const CONTEXT: usize = 3;
+
+/// Unified diff from `old` to `new`, empty if they are the same
+pub fn unified(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let ops: Vec<(Op, &str)> = diff_lines(&old, &new);
+    if ops.iter().all(|(op, _)| *op == Op::Same) {
+        return String::new();
+    }
+
+    let mut diff: String = format!("--- {old_name}\n+++ {new_name}\n");
+
+    //Line numbers in old/new where each op starts, for the hunk headers
+    let mut positions: Vec<(usize, usize)> = Vec::with_capacity(ops.len() + 1);
+    let (mut old_line, mut new_line) = (0, 0);
+    for (op, _) in &ops {
+        positions.push((old_line, new_line));
+        if *op != Op::Added { old_line += 1; }
+        if *op != Op::Removed { new_line += 1; }
+    }
+    positions.push((old_line, new_line));
+
+    let changed: Vec<usize> = (0..ops.len()).filter(|&i| ops[i].0 != Op::Same).collect();
+    let mut i: usize = 0;
+    while i < changed.len() {
+        //Grow the hunk while the next change is close enough for the contexts to touch
+        let mut j: usize = i;
+        while j + 1 < changed.len() && changed[j + 1] - changed[j] <= 2 * CONTEXT {
+            j += 1;
+        }
+        let start: usize = changed[i].saturating_sub(CONTEXT);
+        let end: usize = (changed[j] + CONTEXT + 1).min(ops.len());
+
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        diff.push_str(&format!("@@ -{} +{} @@\n", range(old_start, old_end - old_start), range(new_start, new_end - new_start)));
+        for (op, line) in &ops[start..end] {
+            let sign: char = match op { Op::Same => ' ', Op::Removed => '-', Op::Added => '+' };
+            diff.push_str(&format!("{sign}{line}\n"));
+        }
+        i = j + 1;
+    }
+    diff
+}
+
+/// `start,len` with 1-based start, like diff -u
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+/// Longest common subsequence walk, fine for the size of a tree
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let mut lcs: Vec<Vec<usize>> = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let mut ops: Vec<(Op, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((Op::Same, old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push((Op::Removed, old[i]));
+            i += 1;
+        } else {
+            ops.push((Op::Added, new[j]));
+            j += 1;
+        }
+    }
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unified;
+
+    #[test]
+    fn same_text_has_no_diff() {
+        assert_eq!(unified("a\nb\n", "a\nb", "old", "new"), "");
+        assert_eq!(unified("", "", "old", "new"), "");
+    }
+
+    #[test]
+    fn changed_line() {
+        assert_eq!(unified("a\nb\nc", "a\nx\nc", "old", "new"), "--- old\n+++ new\n@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n");
+    }
+
+    #[test]
+    fn from_and_to_empty() {
+        assert_eq!(unified("", "a\nb", "old", "new"), "--- old\n+++ new\n@@ -0,0 +1,2 @@\n+a\n+b\n");
+        assert_eq!(unified("a", "", "old", "new"), "--- old\n+++ new\n@@ -1 +0,0 @@\n-a\n");
+    }
+
+    #[test]
+    fn context_is_three_lines() {
+        let old: String = (1..=10).map(|i| format!("{i}\n")).collect();
+        let new: String = old.replace("5\n", "five\n");
+        assert_eq!(unified(&old, &new, "old", "new"), "--- old\n+++ new\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n");
+    }
+
+    #[test]
+    fn far_apart_changes_get_their_own_hunks() {
+        let old: String = (1..=20).map(|i| format!("{i}\n")).collect();
+        let new: String = old.replacen("2\n", "two\n", 1).replace("\n19\n", "\nnineteen\n");
+        let diff: String = unified(&old, &new, "old", "new");
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n 1\n-2\n+two\n"));
+        assert!(diff.contains("@@ -16,5 +16,5 @@\n 16\n 17\n 18\n-19\n+nineteen\n 20\n"));
+    }
+
+    #[test]
+    fn close_changes_share_a_hunk() {
+        let old: String = (1..=12).map(|i| format!("{i}\n")).collect();
+        let new: String = old.replace("\n3\n", "\nthree\n").replace("\n9\n", "\nnine\n");
+        assert_eq!(unified(&old, &new, "old", "new").matches("@@ -").count(), 1);
+    }
+}
@@ -0,0 +1,129 @@
+//! `--contents`, the files themselves after the tree, for pasting a whole project into an LLM prompt.
+
+use crate::content;
+use crate::entry::{self, Entry};
+use crate::style;
+use std::fs;
+use std::path::Path;
+
+/// Files bigger than this are listed but not included, they'd drown out everything else
+const MAX_BYTES: u64 = 1 << 20;
+
+/// What can be shown of a file
+pub enum FileText {
+    Text(String),
+    Binary,
+    TooLarge(u64),
+    Unreadable,
+}
+
+impl FileText {
+    /// Only regular files are opened, FIFOs and devices count as unreadable
+    pub fn read(path: &Path) -> FileText {
+        match fs::metadata(path) {
+            Ok(metadata) if !metadata.is_file() => FileText::Unreadable,
+            Ok(metadata) if metadata.len() > MAX_BYTES => FileText::TooLarge(metadata.len()),
+            Ok(_) => content::read_text(path).map_or(FileText::Binary, FileText::Text),
+            Err(_) => FileText::Unreadable,
+        }
+    }
+
+    /// Why the contents are left out
+    pub fn placeholder(&self) -> Option<String> {
+        match self {
+            FileText::Text(_) => None,
+            FileText::Binary => Some(String::from("[binary, not included]")),
+            FileText::TooLarge(bytes) => Some(format!("[{}, not included]", crate::size::human(*bytes))),
+            FileText::Unreadable => Some(String::from("[cannot read]")),
+        }
+    }
+}
+
+/// Every file in tree order, each under its path in a code fence, with estimated token counts so far so it's clear
+/// where a context window runs out
+pub fn render(entries: &[Entry]) -> String {
+    let mut sections: Vec<String> = Vec::new();
+    let mut total: usize = 0;
+    //Only files whose contents are included, not the ones listed with a placeholder
+    let mut included: usize = 0;
+    entry::for_each_file(entries, &mut |entry| {
+        let text: FileText = FileText::read(Path::new(&entry.path));
+        let section: String = match (&text, text.placeholder()) {
+            (FileText::Text(contents), _) => {
+                let tokens: usize = estimate_tokens(contents);
+                total += tokens;
+                included += 1;
+                let fence: String = fence_for(contents);
+                let language: &str = entry.name.rsplit_once('.').map_or("", |(_, extension)| extension);
+                format!(
+                    "{} (~{} tokens, {} total)\n{fence}{language}\n{}\n{fence}",
+                    style::escape_control(&entry.path),
+                    thousands(tokens),
+                    thousands(total),
+                    contents.trim_end_matches('\n'),
+                )
+            }
+            (_, placeholder) => format!("{} {}", style::escape_control(&entry.path), placeholder.unwrap_or_default()),
+        };
+        sections.push(section);
+    });
+    sections.push(format!("~{} tokens in {included} files", thousands(total)));
+    sections.join("\n\n")
+}
+
+/// Every file as `<file path="...">contents</file>`, the layout LLM vendors recommend for documents in a prompt.
+/// Contents go in unescaped, models read them as they are and escaping would mangle the code
+pub fn render_xml(entries: &[Entry]) -> String {
+    let mut files: Vec<String> = Vec::new();
+    entry::for_each_file(entries, &mut |entry| {
+        let path: String = xml_attribute(&style::escape_control(&entry.path));
+        let text: FileText = FileText::read(Path::new(&entry.path));
+        files.push(match (&text, text.placeholder()) {
+            (FileText::Text(contents), _) => format!("<file path=\"{path}\">\n{}\n</file>", contents.trim_end_matches('\n')),
+            (_, placeholder) => format!("<file path=\"{path}\" omitted=\"{}\"/>", xml_attribute(&placeholder.unwrap_or_default())),
+        });
+    });
+    files.join("\n")
+}
+
+fn xml_attribute(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Rough count for BPE tokenizers, which make about one token per four letters of a word and one per symbol.
+/// Close enough to tell whether something fits, without shipping a vocabulary
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens: usize = 0;
+    let mut word: usize = 0;
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            word += 1;
+            continue;
+        }
+        tokens += word.div_ceil(4);
+        word = 0;
+        if !c.is_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens + word.div_ceil(4)
+}
+
+/// `12,345`
+pub fn thousands(n: usize) -> String {
+    let digits: String = n.to_string();
+    let mut grouped: String = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Backticks, one more than the longest run inside, so a markdown file can't close the fence early
+pub fn fence_for(contents: &str) -> String {
+    let longest: usize = contents.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat((longest + 1).max(3))
+}
@@ -0,0 +1,39 @@
+//! Build output and cache directories of common ecosystems, stopped at by default when the
+//! project root has the matching manifest.
+
+use std::path::Path;
+
+struct Ecosystem {
+    /// Any of these in the root means the project uses this ecosystem
+    manifests: &'static [&'static str],
+    /// Directory names not to recurse into, at any depth
+    stop: &'static [&'static str],
+}
+
+//node_modules isn't here, it is stopped at regardless (see --node-modules)
+const ECOSYSTEMS: &[Ecosystem] = &[
+    //Rust
+    Ecosystem { manifests: &["Cargo.toml"], stop: &["target"] },
+    //JavaScript / TypeScript
+    Ecosystem { manifests: &["package.json"], stop: &["dist", ".next", ".nuxt", ".turbo", ".parcel-cache", ".svelte-kit", "coverage"] },
+    //Python
+    Ecosystem { manifests: &["pyproject.toml", "setup.py", "setup.cfg", "requirements.txt", "Pipfile"], stop: &["__pycache__", ".venv", "venv", ".tox", ".pytest_cache", ".mypy_cache", ".ruff_cache"] },
+    //Java / Kotlin
+    Ecosystem { manifests: &["pom.xml", "build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"], stop: &["build", ".gradle", "target"] },
+    //Dart / Flutter
+    Ecosystem { manifests: &["pubspec.yaml"], stop: &[".dart_tool", "build"] },
+    //Swift
+    Ecosystem { manifests: &["Package.swift"], stop: &[".build"] },
+    //Elixir
+    Ecosystem { manifests: &["mix.exs"], stop: &["_build", "deps"] },
+    //Ruby
+    Ecosystem { manifests: &["Gemfile"], stop: &[".bundle"] },
+];
+
+/// Directory names to stop at for the ecosystems detected in `root`, may contain duplicates
+pub fn detect_stops(root: &Path) -> Vec<&'static str> {
+    ECOSYSTEMS.iter()
+        .filter(|ecosystem| ecosystem.manifests.iter().any(|manifest| root.join(manifest).is_file()))
+        .flat_map(|ecosystem| ecosystem.stop.iter().copied())
+        .collect()
+}
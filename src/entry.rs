@@ -0,0 +1,75 @@
+//! The scanned tree, kept around so it can be filtered and rendered in several formats.
+
+use crate::content::TodoCounts;
+use crate::loc::LocCounts;
+use crate::style::Style;
+
+/// A scanned file or directory, rendered once the whole tree is known
+pub struct Entry {
+    pub name: String,
+    /// From the root, `/` separated. Absolute with --absolute
+    pub path: String,
+    pub is_dir: bool,
+    /// Shown after the name, e.g. `-> target` or `[fifo]`
+    pub notes: Vec<String>,
+    pub style: Option<Style>,
+    /// Markers in this file, or everything below this directory
+    pub todos: TodoCounts,
+    /// Lines of this file, or everything below this directory
+    pub loc: LocCounts,
+    /// Bytes of this file, or everything below this directory. Only measured when needed
+    pub size: u64,
+    /// What the --exec command printed for this file
+    pub output: String,
+    pub children: Vec<Entry>
+}
+
+impl Entry {
+    /// Name, or path from the root with `full_path`, with a slash for directories
+    pub fn display_name(&self, full_path: bool) -> String {
+        let name: &str = if full_path { &self.path } else { &self.name };
+        format!("{name}{}", if self.is_dir { "/" } else { "" })
+    }
+
+    /// Number of files and directories below this one
+    pub fn count(&self) -> usize {
+        self.children.iter().map(|child| 1 + child.count()).sum()
+    }
+}
+
+/// (files, directories) in the whole tree
+pub fn count_kinds(entries: &[Entry]) -> (usize, usize) {
+    entries.iter().fold((0, 0), |(files, dirs), entry| {
+        let (sub_files, sub_dirs) = count_kinds(&entry.children);
+        if entry.is_dir { (files + sub_files, dirs + sub_dirs + 1) } else { (files + sub_files + 1, dirs + sub_dirs) }
+    })
+}
+
+/// Prefix every path with `root`, once nothing needs them relative anymore
+pub fn absolutize(entries: &mut [Entry], root: &str) {
+    for entry in entries {
+        entry.path = format!("{}/{}", root.trim_end_matches('/'), entry.path);
+        absolutize(&mut entry.children, root);
+    }
+}
+
+/// Path of every entry in tree order, directories with a trailing slash
+pub fn paths(entries: &[Entry]) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for entry in entries {
+        paths.push(entry.display_name(true));
+        paths.extend(self::paths(&entry.children));
+    }
+    paths
+}
+
+/// Calls `f` on every file below `entries`, in tree order
+pub fn for_each_file(entries: &[Entry], f: &mut impl FnMut(&Entry)) {
+    for entry in entries {
+        if entry.is_dir {
+            for_each_file(&entry.children, f);
+        } else {
+            f(entry);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! `--exec`, annotating files with the output of a command run on each of them.
+
+use crate::entry::Entry;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A command taking longer than this is killed and the file marked `[timed out]`
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs `command` with `{}` replaced by each file's path, a few at a time, and stores the trimmed output on the entries
+pub fn annotate(entries: &mut [Entry], command: &str) {
+    let mut paths: Vec<String> = Vec::new();
+    collect_files(entries, &mut paths);
+
+    let workers: usize = thread::available_parallelism().map_or(4, |n| n.get()).min(paths.len().max(1));
+    let next: AtomicUsize = AtomicUsize::new(0);
+    let outputs: Mutex<Vec<String>> = Mutex::new(vec![String::new(); paths.len()]);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let i: usize = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(i) else { break };
+                let output: String = run(command, path);
+                outputs.lock().unwrap()[i] = output;
+            });
+        }
+    });
+
+    let mut outputs = outputs.into_inner().unwrap().into_iter();
+    store(entries, &mut outputs);
+}
+
+/// Files in the same order `store` walks them
+fn collect_files(entries: &[Entry], paths: &mut Vec<String>) {
+    for entry in entries {
+        if entry.is_dir {
+            collect_files(&entry.children, paths);
+        } else {
+            paths.push(entry.path.clone());
+        }
+    }
+}
+
+fn store(entries: &mut [Entry], outputs: &mut impl Iterator<Item = String>) {
+    for entry in entries {
+        if entry.is_dir {
+            store(&mut entry.children, outputs);
+        } else {
+            entry.output = outputs.next().unwrap_or_default();
+        }
+    }
+}
+
+/// Output on one line, or why there is none
+fn run(command: &str, path: &str) -> String {
+    let command: String = command.replace("{}", &quote(path));
+    let child = shell(&command).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn();
+    let Ok(mut child) = child else { return String::from("[exec failed]") };
+
+    //Read on the side so a chatty command can't fill the pipe and stall while we wait
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut output: Vec<u8> = Vec::new();
+        let _ = stdout.read_to_end(&mut output);
+        output
+    });
+
+    if !wait(&mut child) {
+        kill_tree(&mut child);
+        let _ = child.wait();
+        return String::from("[timed out]");
+    }
+    let output: Vec<u8> = reader.join().unwrap_or_default();
+    String::from_utf8_lossy(&output).split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Whether the child exited before the timeout
+fn wait(child: &mut Child) -> bool {
+    let deadline: Instant = Instant::now() + TIMEOUT;
+    while Instant::now() < deadline {
+        match child.try_wait() {
+            Ok(Some(_)) | Err(_) => return true,
+            Ok(None) => thread::sleep(Duration::from_millis(5)),
+        }
+    }
+    false
+}
+
+/// In a process group of its own, so a timeout can take down everything the command started
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+    let mut shell: Command = Command::new("sh");
+    shell.arg("-c").arg(command).process_group(0);
+    shell
+}
+
+/// cmd doesn't follow the usual argument quoting, the command goes through as it is
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut shell: Command = Command::new("cmd");
+    shell.arg("/C").raw_arg(command);
+    shell
+}
+
+#[cfg(not(any(unix, windows)))]
+fn shell(command: &str) -> Command {
+    let mut shell: Command = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(unix)]
+fn kill_tree(child: &mut Child) {
+    //SAFETY: plain syscall, the negative pid addresses the group `shell` put the command in
+    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+}
+
+/// taskkill /T takes the children along, the plain kill is for when it isn't there
+#[cfg(not(unix))]
+fn kill_tree(child: &mut Child) {
+    let killed: bool = Command::new("taskkill").args(["/T", "/F", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null()).stderr(Stdio::null()).status().is_ok_and(|status| status.success());
+    if !killed {
+        let _ = child.kill();
+    }
+}
+
+/// `./` keeps names starting with `-` from being read as flags
+#[cfg(unix)]
+fn quote(path: &str) -> String {
+    format!("'./{}'", path.replace('\'', r"'\''"))
+}
+
+/// `"` can't be in a Windows name, but `%` still expands inside quotes, so it goes outside them escaped as `^%`
+#[cfg(not(unix))]
+fn quote(path: &str) -> String {
+    format!("\".\\{}\"", path.replace('/', "\\").replace('%', "\"^%\""))
+}
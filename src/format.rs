@@ -0,0 +1,739 @@
+//! Renderers turning the scanned entries into text.
+
+use crate::compat;
+use crate::diagram;
+use crate::dump;
+use crate::entry::{self, Entry};
+use crate::icons;
+use crate::ncdu;
+use crate::platform;
+use crate::repo::RepoLinks;
+use crate::size;
+use crate::style;
+use clap::ValueEnum;
+use std::borrow::Cow;
+use std::path::{Component, PathBuf};
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// The ascii tree
+    #[default]
+    Text,
+    /// One path per line, like `find`
+    List,
+    /// Nested objects with name, type, notes and children
+    Json,
+    /// Nested markdown list
+    Md,
+    /// The tree followed by every file's contents in <file path="..."> tags, for LLM prompts
+    PromptXml,
+    /// An HTML page with every directory in a collapsible <details> block
+    Html,
+    /// A self-contained HTML report with search, expand/collapse all and size bars
+    HtmlReport,
+    /// Graphviz digraph, directories as folders and files as notes
+    Dot,
+    /// Mermaid flowchart, rendered natively by GitHub, GitLab and Notion
+    Mermaid,
+    /// PlantUML Salt tree
+    Plantuml,
+    /// Vector graphics of the tree, monospace text with branch lines
+    Svg,
+    /// LaTeX \dirtree, for figures in papers and theses
+    Latex,
+    /// Emacs Org outline, sizes in property drawers with --size
+    Org,
+    /// Nested AsciiDoc list
+    Asciidoc,
+    /// The ascii tree in a reStructuredText code block, for Sphinx
+    Rst,
+    /// ncdu's JSON export, to browse with `ncdu -f`
+    Ncdu,
+    /// Exactly like GNU `tree -J`, also --compat tree-json
+    TreeJson,
+    /// Exactly like GNU `tree -X`, also --compat tree-xml
+    TreeXml,
+    /// Like Windows' `tree /F /A`: files before folders, +--- and \--- connectors, under the volume header
+    WinTree,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Charset {
+    /// Box drawing lines
+    #[default]
+    Unicode,
+    /// |-- and `-- like `tree --charset ascii`, for places that mangle anything else
+    Ascii,
+    /// ╰── for the last entry
+    Rounded,
+    /// ╠══ ╚══ ║
+    Double,
+    /// Heavy lines, ┣━━ ┗━━ ┃
+    Bold,
+    /// The ones given with --glyphs
+    Custom,
+}
+
+impl Charset {
+    pub fn glyphs(self) -> Glyphs {
+        let (branch, last, pipe) = match self {
+            Charset::Unicode | Charset::Custom => ("├── ", "└── ", "│   "),
+            Charset::Ascii => ("|-- ", "`-- ", "|   "),
+            Charset::Rounded => ("├── ", "╰── ", "│   "),
+            Charset::Double => ("╠══ ", "╚══ ", "║   "),
+            Charset::Bold => ("┣━━ ", "┗━━ ", "┃   "),
+        };
+        Glyphs { branch: branch.to_owned(), last: last.to_owned(), pipe: pipe.to_owned(), blank: String::from("    ") }
+    }
+}
+
+/// The guides of the text tree, all four as wide as each other
+#[derive(Clone, Debug)]
+pub struct Glyphs {
+    /// Before an entry with more after it
+    pub branch: String,
+    /// Before the last entry of a directory
+    pub last: String,
+    /// Below an entry with more after it, down to its next sibling
+    pub pipe: String,
+    /// Below the last entry
+    pub blank: String,
+}
+
+impl Glyphs {
+    /// Plain indentation, for --no-lines
+    pub fn indent() -> Glyphs {
+        let blank: String = String::from("    ");
+        Glyphs { branch: blank.clone(), last: blank.clone(), pipe: blank.clone(), blank }
+    }
+
+    /// `BRANCH,LAST,PIPE[,BLANK]` for --glyphs, e.g. `├─ ,└─ ,│  `. The blank one defaults to spaces
+    pub fn parse(spec: &str) -> Result<Glyphs, String> {
+        let parts: Vec<&str> = spec.split(',').collect();
+        let (branch, last, pipe, blank): (&str, &str, &str, String) = match parts[..] {
+            [branch, last, pipe] => (branch, last, pipe, " ".repeat(style::width(pipe))),
+            [branch, last, pipe, blank] => (branch, last, pipe, blank.to_owned()),
+            _ => return Err(String::from("expected BRANCH,LAST,PIPE or BRANCH,LAST,PIPE,BLANK")),
+        };
+        //Otherwise the levels wouldn't line up
+        if [last, pipe, &blank].iter().any(|glyph| style::width(glyph) != style::width(branch)) {
+            return Err(String::from("the glyphs must all be equally wide"));
+        }
+        Ok(Glyphs { branch: branch.to_owned(), last: last.to_owned(), pipe: pipe.to_owned(), blank })
+    }
+}
+
+impl Default for Glyphs {
+    fn default() -> Glyphs {
+        Charset::default().glyphs()
+    }
+}
+
+/// How entries are drawn, shared by the formats
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    /// Keep the ANSI styling, text only
+    pub color: bool,
+    /// Paths from the root instead of names
+    pub full_path: bool,
+    /// Names in double quotes, with `"` and `\` escaped
+    pub quote: bool,
+    /// Leave control characters in names and notes as they are instead of escaping them
+    pub literal: bool,
+    /// After directory names, `/` when not set
+    pub dir_suffix: Option<String>,
+    /// Guides of the text tree
+    pub glyphs: Glyphs,
+    /// `level 2: src/main.rs` lines instead of the guides, which screen readers read out as gibberish, text only
+    pub accessible: bool,
+    /// Absolute root to make entries OSC 8 file links against, text only
+    pub hyperlink_base: Option<PathBuf>,
+    /// Link entries to the repository's web UI, markdown only
+    pub repo_links: Option<RepoLinks>,
+    /// 📁 and file type emoji before names
+    pub emoji: bool,
+    /// Nerd Font glyphs before names, text only
+    pub icons: bool,
+    /// Annotate with sizes, du style for directories
+    pub size: bool,
+    /// Annotate with TODO/FIXME/HACK counts
+    pub todos: bool,
+    /// Annotate with line counts
+    pub loc: bool,
+    /// Annotate files with their --exec output
+    pub exec: bool,
+    /// Right justify the annotations into columns after the tree instead of appending them to each line, text only
+    pub align: bool,
+    /// Ellipsize lines that would be wider than this, text only
+    pub max_width: Option<usize>,
+    /// Wrap lines past `max_width` onto continuation lines instead of ellipsizing them
+    pub wrap: bool,
+}
+
+/// A text line before the annotations get laid out
+struct Row {
+    text: String,
+    /// Columns taken by the guides before the name
+    indent: usize,
+    /// Guides to start wrapped lines with
+    continuation: String,
+    /// One per enabled annotation, empty when there's nothing to show
+    cells: Vec<String>,
+}
+
+impl Format {
+    /// Renders `entries`, under a `root` directory line if given
+    pub fn render(self, entries: &[Entry], root: Option<&str>, options: &RenderOptions) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        match self {
+            Format::Text => {
+                let mut rows: Vec<Row> = Vec::new();
+                rows.extend(root.map(|root| Row { text: root.to_owned(), indent: 0, continuation: String::new(), cells: Vec::new() }));
+                render_text(entries, "", root.is_some(), 1, options, &mut rows);
+                lines = layout(rows, options);
+            }
+            Format::Json => {
+                let children: String = json_array(entries, options);
+                lines.push(match root {
+                    Some(root) => format!("{{\"name\":{},\"type\":\"directory\",\"children\":{children}}}", json_string(root)),
+                    None => children,
+                });
+            }
+            Format::Md => {
+                lines.extend(root.map(|root| format!("- `{root}{}`", options.dir_suffix.as_deref().unwrap_or("/"))));
+                render_md(entries, if root.is_some() { 1 } else { 0 }, options, &mut lines);
+            }
+            Format::PromptXml => {
+                let plain = RenderOptions { color: false, hyperlink_base: None, max_width: None, ..options.clone() };
+                lines.push(format!("<tree>\n{}\n</tree>", Format::Text.render(entries, root, &plain)));
+                lines.push(dump::render_xml(entries));
+            }
+            Format::Html => {
+                let title: String = html_escape(root.unwrap_or("project-tree"));
+                lines.push(format!("<!doctype html>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{HTML_STYLE}</style>"));
+                if let Some(root) = root {
+                    lines.push(format!("<p><strong>{}</strong></p>", html_escape(root)));
+                }
+                render_html(entries, 0, None, options, &mut lines);
+            }
+            Format::HtmlReport => {
+                let title: String = html_escape(root.unwrap_or("project-tree"));
+                let (files, dirs) = entry::count_kinds(entries);
+                let total: u64 = entries.iter().map(|entry| entry.size).sum();
+                lines.push(format!("<!doctype html>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{HTML_STYLE} {REPORT_STYLE}</style>"));
+                lines.push(format!("<h1>{title}</h1>\n<p>{files} files, {dirs} directories, {}</p>", size::human(total)));
+                lines.push(String::from("<p><input id=\"search\" type=\"search\" placeholder=\"Search\" autofocus> \
+                    <button id=\"expand\">Expand all</button> <button id=\"collapse\">Collapse all</button></p>"));
+                render_html(entries, 0, Some(total), options, &mut lines);
+                lines.push(format!("<script>{REPORT_SCRIPT}</script>"));
+            }
+            Format::List => {
+                //The path is all there is to tell where an entry is
+                let paths = RenderOptions { full_path: true, ..options.clone() };
+                render_list(entries, &paths, &mut lines);
+            }
+            Format::Dot => lines.push(diagram::dot(entries, root, options.full_path)),
+            Format::Mermaid => lines.push(diagram::mermaid(entries, root, options.full_path)),
+            Format::Plantuml => lines.push(diagram::plantuml(entries, root, options.full_path)),
+            Format::Latex => lines.push(diagram::latex(entries, root, options.full_path)),
+            Format::Rst => {
+                //A nested rst list needs blank lines around every level, the tree itself reads better in a literal block
+                let plain = RenderOptions { color: false, hyperlink_base: None, max_width: None, ..options.clone() };
+                let tree: String = Format::Text.render(entries, root, &plain);
+                let indented: Vec<String> = tree.lines().map(|line| format!("   {line}").trim_end().to_owned()).collect();
+                lines.push(format!(".. code-block:: text\n\n{}", indented.join("\n")));
+            }
+            Format::Asciidoc => {
+                lines.extend(root.map(|root| format!("* `+{root}{}+`", options.dir_suffix.as_deref().unwrap_or("/"))));
+                render_asciidoc(entries, if root.is_some() { 2 } else { 1 }, options, &mut lines);
+            }
+            Format::Org => {
+                lines.extend(root.map(|root| format!("* {}", org_verbatim(&format!("{root}{}", options.dir_suffix.as_deref().unwrap_or("/"))))));
+                //The size goes in the drawer rather than the headline
+                let headline = RenderOptions { size: false, ..options.clone() };
+                render_org(entries, if root.is_some() { 2 } else { 1 }, options, &headline, &mut lines);
+            }
+            Format::Svg => {
+                let mut rows: Vec<SvgRow> = Vec::new();
+                if let Some(root) = root {
+                    rows.push(SvgRow { depth: 0, parent: None, label: html_escape(&format!("{root}{}", options.dir_suffix.as_deref().unwrap_or("/"))), width: style::width(root) + 1 });
+                }
+                svg_rows(entries, if root.is_some() { 1 } else { 0 }, root.map(|_| 0), options, &mut rows);
+                lines.push(render_svg(&rows));
+            }
+            Format::Ncdu => lines.push(ncdu::render(entries, &std::env::current_dir().unwrap_or_default())),
+            Format::TreeJson => lines.push(compat::json(entries)),
+            Format::TreeXml => lines.push(compat::xml(entries, options.literal)),
+            Format::WinTree => {
+                let serial: u32 = platform::volume_serial().unwrap_or(0);
+                //`C:` on Windows, nothing elsewhere
+                let drive: String = std::env::current_dir().ok()
+                    .and_then(|dir| match dir.components().next() {
+                        Some(Component::Prefix(prefix)) => Some(prefix.as_os_str().to_string_lossy().into_owned()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                lines.push(String::from("Folder PATH listing"));
+                lines.push(format!("Volume serial number is {:04X}-{:04X}", serial >> 16, serial & 0xFFFF));
+                lines.push(format!("{drive}."));
+                render_win_tree(entries, "", options, &mut lines);
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Draws `entries` as ascii tree lines
+fn render_text(entries: &[Entry], cur_prefix: &str, show_lines: bool, depth: usize, options: &RenderOptions, rows: &mut Vec<Row>) {
+    for (i, entry) in entries.iter().enumerate() {
+        if options.accessible {
+            let level: String = format!("level {depth}: ");
+            rows.push(Row {
+                indent: level.len(),
+                continuation: " ".repeat(level.len()),
+                text: format!("{level}{}", label(entry, options)),
+                cells: annotations(entry, options),
+            });
+            render_text(&entry.children, "", false, depth + 1, options, rows);
+            continue;
+        }
+
+        let is_last: bool = i == entries.len() - 1;
+        let glyphs: &Glyphs = &options.glyphs;
+
+        let affix: &str = match (show_lines, is_last) {
+            (true, true) => &glyphs.last,
+            (true, false) => &glyphs.branch,
+            (false, _) => "",
+        };
+        let new_prefix = format!("{cur_prefix}{}", if is_last { &glyphs.blank } else { &glyphs.pipe });
+        //Keeps the line down to the children going
+        let down: String = match glyphs.pipe.chars().next() {
+            Some(line) if !entry.children.is_empty() => format!("{line} "),
+            _ => String::from("  "),
+        };
+        rows.push(Row {
+            text: format!("{cur_prefix}{affix}{}", label(entry, options)),
+            indent: style::width(cur_prefix) + style::width(affix),
+            continuation: format!("{new_prefix}{down}"),
+            cells: annotations(entry, options),
+        });
+
+        render_text(&entry.children, &new_prefix, true, depth + 1, options, rows);
+    }
+}
+
+/// Appends the annotations to their lines, or with `align` pads every line to the widest one and right justifies each annotation
+/// in its own column. Needs all the rows up front, as any line further down can widen a column.
+///
+/// With a `max_width` the names get ellipsized so the annotations still fit on the line, unless that would leave less than
+/// a few characters of the name, then the annotations get cut instead. With `wrap` the rest goes on continuation lines
+fn layout(rows: Vec<Row>, options: &RenderOptions) -> Vec<String> {
+    const MIN_NAME: usize = 8;
+    let fit = |text: &str, indent: usize, cells_width: usize| -> String {
+        match options.max_width {
+            Some(max) if !options.wrap => style::truncate(text, max.saturating_sub(cells_width).max(indent + MIN_NAME)),
+            _ => text.to_owned(),
+        }
+    };
+    let finish = |line: String, continuation: &str| -> String {
+        match options.max_width {
+            Some(max) if options.wrap => wrap(&line, continuation, max),
+            Some(max) => style::truncate(&line, max),
+            None => line,
+        }
+    };
+
+    if !options.align {
+        return rows.into_iter().map(|row| {
+            let cells: Vec<&String> = row.cells.iter().filter(|cell| !cell.is_empty()).collect();
+            let cells_width: usize = cells.iter().map(|cell| 1 + style::width(cell)).sum();
+            let mut line: String = fit(&row.text, row.indent, cells_width);
+            for cell in cells {
+                line.push(' ');
+                line.push_str(cell);
+            }
+            finish(line, &row.continuation)
+        }).collect();
+    }
+
+    let mut cell_widths: Vec<usize> = Vec::new();
+    for row in &rows {
+        cell_widths.resize(cell_widths.len().max(row.cells.len()), 0);
+        for (width, cell) in cell_widths.iter_mut().zip(&row.cells) {
+            *width = (*width).max(style::width(cell));
+        }
+    }
+    let cells_width: usize = cell_widths.iter().map(|width| 2 + width).sum();
+    //Lines without annotations can use the whole width, and don't widen the name column
+    let has_cells = |row: &Row| row.cells.iter().any(|cell| !cell.is_empty());
+    let rows: Vec<Row> = rows.into_iter().map(|row| {
+        let text: String = fit(&row.text, row.indent, if has_cells(&row) { cells_width } else { 0 });
+        Row { text, ..row }
+    }).collect();
+    let text_width: usize = rows.iter().filter(|row| has_cells(row)).map(|row| style::width(&row.text)).max().unwrap_or(0);
+
+    rows.into_iter().map(|row| {
+        if !has_cells(&row) {
+            return row.text;
+        }
+        let mut line: String = format!("{}{}", row.text, " ".repeat(text_width - style::width(&row.text)));
+        for (cell, width) in row.cells.iter().zip(&cell_widths) {
+            line.push_str(&format!("  {}{cell}", " ".repeat(width - style::width(cell))));
+        }
+        finish(line, &row.continuation)
+    }).collect()
+}
+
+/// Breaks `line` into pieces no wider than `max`, each one after the first starting with `continuation`
+fn wrap(line: &str, continuation: &str, max: usize) -> String {
+    let (mut wrapped, mut rest) = style::split(line, max);
+    //Past the guides there has to be room for something
+    let room: usize = max.saturating_sub(style::width(continuation)).max(1);
+    while !rest.is_empty() {
+        let (piece, tail) = style::split(rest.trim_start_matches(' '), room);
+        wrapped.push_str(&format!("\n{continuation}{piece}"));
+        rest = tail;
+    }
+    wrapped
+}
+
+/// One cell per enabled annotation
+fn annotations(entry: &Entry, options: &RenderOptions) -> Vec<String> {
+    let mut cells: Vec<String> = Vec::new();
+    if options.size {
+        cells.push(format!("[{}]", size::human(entry.size)));
+    }
+    if options.todos {
+        cells.push(if entry.todos.is_empty() { String::new() } else { entry.todos.to_string() });
+    }
+    if options.loc {
+        cells.push(if entry.loc.is_empty() { String::new() } else { entry.loc.to_string() });
+    }
+    if options.exec {
+        cells.push(entry.output.clone());
+    }
+    cells
+}
+
+/// Name or path with the suffix for directories
+fn shown_name(entry: &Entry, options: &RenderOptions) -> String {
+    let suffix: &str = if entry.is_dir { options.dir_suffix.as_deref().unwrap_or("/") } else { "" };
+    format!("{}{suffix}", bare_name(entry, options))
+}
+
+/// Name or path, escaped unless `literal` and quoted with `quote`
+fn bare_name(entry: &Entry, options: &RenderOptions) -> String {
+    let name: Cow<str> = printable(if options.full_path { &entry.path } else { &entry.name }, options);
+    if options.quote { quote(&name) } else { name.into_owned() }
+}
+
+fn printable<'a>(text: &'a str, options: &RenderOptions) -> Cow<'a, str> {
+    if options.literal { Cow::Borrowed(text) } else { style::escape_control(text) }
+}
+
+/// `"name"`, like `tree -Q`
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Name with the notes, as shown in the tree
+fn label(entry: &Entry, options: &RenderOptions) -> String {
+    let mut label: String = shown_name(entry, options);
+    if let Some(base) = &options.hyperlink_base {
+        label = style::hyperlink(&file_url(&base.join(&entry.path)), &label);
+    }
+    if options.emoji {
+        label = format!("{} {label}", icons::emoji(&entry.name, entry.is_dir));
+    } else if options.icons {
+        label = format!("{} {label}", icons::glyph(&entry.name, entry.is_dir));
+    }
+    for note in &entry.notes {
+        label.push(' ');
+        label.push_str(&printable(note, options));
+    }
+    match entry.style {
+        Some(style) if options.color => style.paint(&label),
+        _ => label,
+    }
+}
+
+/// Every entry's path on a line of its own, in tree order
+fn render_list(entries: &[Entry], options: &RenderOptions, lines: &mut Vec<String>) {
+    for entry in entries {
+        lines.push(shown_name(entry, options));
+        render_list(&entry.children, options, lines);
+    }
+}
+
+/// `tree /F` lists a directory's files first, under the guide down to its folders, then a spacer line
+fn render_win_tree(entries: &[Entry], prefix: &str, options: &RenderOptions, lines: &mut Vec<String>) {
+    let (dirs, files): (Vec<&Entry>, Vec<&Entry>) = entries.iter().partition(|entry| entry.is_dir);
+    let guide: &str = if dirs.is_empty() { "    " } else { "|   " };
+    for file in &files {
+        lines.push(format!("{prefix}{guide}{}", plain_label(file, options)));
+    }
+    if !files.is_empty() {
+        lines.push(format!("{prefix}{guide}"));
+    }
+    for (i, dir) in dirs.iter().enumerate() {
+        let is_last: bool = i == dirs.len() - 1;
+        lines.push(format!("{prefix}{}{}", if is_last { "\\---" } else { "+---" }, plain_label(dir, options)));
+        render_win_tree(&dir.children, &format!("{prefix}{}", if is_last { "    " } else { "|   " }), options, lines);
+    }
+}
+
+const HTML_STYLE: &str = "ul { list-style: none; padding-left: 1.2em; margin: 0 } \
+    body { font-family: ui-monospace, monospace; line-height: 1.4 } summary { cursor: pointer } .note { color: #888 }";
+
+const REPORT_STYLE: &str = ".size { display: inline-block; width: 9em; } \
+    .bar { display: inline-block; width: 4em; height: 0.7em; background: #eee; margin-right: 0.5em } \
+    .bar span { display: block; height: 100%; background: #6a9fd4 } [hidden] { display: none }";
+
+/// Search shows the matching entries and the directories leading to them
+const REPORT_SCRIPT: &str = "\
+const search = document.getElementById('search');
+const items = [...document.querySelectorAll('li')];
+search.addEventListener('input', () => {
+  const query = search.value.toLowerCase();
+  for (const item of items) item.hidden = query !== '';
+  if (query === '') return;
+  for (const item of items.filter(item => item.dataset.path.toLowerCase().includes(query))) {
+    for (let node = item; node && node.tagName !== 'BODY'; node = node.parentElement) {
+      if (node.tagName === 'LI') node.hidden = false;
+      if (node.tagName === 'DETAILS' && node.parentElement !== item) node.open = true;
+    }
+  }
+});
+const setOpen = open => document.querySelectorAll('details').forEach(details => details.open = open);
+document.getElementById('expand').onclick = () => setOpen(true);
+document.getElementById('collapse').onclick = () => setOpen(false);";
+
+/// Every directory is a `<details>` block, the top level ones start out open. With a `total` size (the report)
+/// each entry gets a bar for its share of it
+fn render_html(entries: &[Entry], depth: usize, total: Option<u64>, options: &RenderOptions, lines: &mut Vec<String>) {
+    let indent: String = "  ".repeat(depth);
+    lines.push(format!("{indent}<ul>"));
+    for entry in entries {
+        let mut label: String = html_escape(&shown_name(entry, options));
+        //Inline styles can't garble anything, so they're kept whether or not the terminal gets colors
+        if let Some(style) = entry.style {
+            label = format!("<span style=\"{}\">{label}</span>", style.css());
+        }
+        let notes: Vec<String> = entry.notes.iter().chain(&annotations(entry, options))
+            .filter(|note| !note.is_empty())
+            .map(|note| html_escape(&printable(note, options)))
+            .collect();
+        if !notes.is_empty() {
+            label = format!("{label} <span class=\"note\">{}</span>", notes.join(" "));
+        }
+        let mut item: String = String::from("<li");
+        if let Some(total) = total {
+            let share: f64 = if total == 0 { 0.0 } else { entry.size as f64 / total as f64 * 100.0 };
+            item = format!("<li data-path=\"{}\"", html_escape(&printable(&entry.path, options)));
+            label = format!("<span class=\"size\"><span class=\"bar\"><span style=\"width: {share:.1}%\"></span></span>{}</span>{label}", size::human(entry.size));
+        }
+        if entry.children.is_empty() {
+            lines.push(format!("{indent}{item}>{label}</li>"));
+            continue;
+        }
+        lines.push(format!("{indent}{item}><details{}><summary>{label}</summary>", if depth == 0 { " open" } else { "" }));
+        render_html(&entry.children, depth + 1, total, options, lines);
+        lines.push(format!("{indent}</details></li>"));
+    }
+    lines.push(format!("{indent}</ul>"));
+}
+
+/// A line of the SVG, escaped and styled already
+struct SvgRow {
+    depth: usize,
+    /// Row of the parent, which the branch line comes from
+    parent: Option<usize>,
+    label: String,
+    /// In characters
+    width: usize,
+}
+
+fn svg_rows(entries: &[Entry], depth: usize, parent: Option<usize>, options: &RenderOptions, rows: &mut Vec<SvgRow>) {
+    for entry in entries {
+        let name: String = shown_name(entry, options);
+        let mut width: usize = style::width(&name);
+        let mut label: String = html_escape(&name);
+        if let Some(style) = entry.style {
+            label = format!("<tspan style=\"{}\">{label}</tspan>", style.svg_css());
+        }
+        let notes: Vec<String> = entry.notes.iter().chain(&annotations(entry, options))
+            .filter(|note| !note.is_empty())
+            .map(|note| printable(note, options).into_owned())
+            .collect();
+        if !notes.is_empty() {
+            let notes: String = notes.join(" ");
+            width += 1 + style::width(&notes);
+            label = format!("{label} <tspan class=\"note\">{}</tspan>", html_escape(&notes));
+        }
+        rows.push(SvgRow { depth, parent, label, width });
+        let row: usize = rows.len() - 1;
+        svg_rows(&entry.children, depth + 1, Some(row), options, rows);
+    }
+}
+
+/// Rows of monospace text on a light background (so it reads on dark pages too), with a branch line from every entry's
+/// parent. Sizes are in pixels for 14px text, whose characters are about 8.4 wide
+fn render_svg(rows: &[SvgRow]) -> String {
+    const PAD: f64 = 12.0;
+    const ROW: f64 = 20.0;
+    const INDENT: f64 = 20.0;
+    const CHAR: f64 = 8.4;
+    let middle = |row: usize| PAD + row as f64 * ROW + ROW / 2.0;
+    let mut paths: Vec<String> = Vec::new();
+    let mut texts: Vec<String> = Vec::new();
+    let mut width: f64 = 0.0;
+    for (i, row) in rows.iter().enumerate() {
+        let x: f64 = PAD + row.depth as f64 * INDENT;
+        if let Some(parent) = row.parent {
+            //Down from below the parent's name, then across to this one
+            let trunk: f64 = x - INDENT + 5.0;
+            paths.push(format!("M{trunk} {}V{}H{}", PAD + (parent + 1) as f64 * ROW - 2.0, middle(i), x - 3.0));
+        }
+        texts.push(format!("<text x=\"{x}\" y=\"{}\">{}</text>", middle(i) + 5.0, row.label));
+        width = width.max(x + row.width as f64 * CHAR);
+    }
+    let width: f64 = (width + PAD).ceil();
+    let height: f64 = PAD * 2.0 + rows.len() as f64 * ROW;
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n\
+        <style>text {{ font: 14px ui-monospace, Menlo, Consolas, monospace; fill: #24292f; white-space: pre }} .note {{ fill: #888 }}</style>\n\
+        <rect width=\"100%\" height=\"100%\" rx=\"6\" fill=\"#f6f8fa\"/>\n\
+        <path d=\"{}\" fill=\"none\" stroke=\"#8c959f\"/>\n{}\n</svg>",
+        paths.join(""),
+        texts.join("\n"),
+    )
+}
+
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Name without a slash, then the notes and annotations
+fn plain_label(entry: &Entry, options: &RenderOptions) -> String {
+    let mut label: String = bare_name(entry, options);
+    for note in entry.notes.iter().chain(&annotations(entry, options)).filter(|note| !note.is_empty()) {
+        label.push(' ');
+        label.push_str(&printable(note, options));
+    }
+    label
+}
+
+/// Names go in backticks so underscores and asterisks in them aren't read as emphasis
+fn render_md(entries: &[Entry], depth: usize, options: &RenderOptions, lines: &mut Vec<String>) {
+    for entry in entries {
+        let name: String = format!("`{}`", shown_name(entry, options));
+        let name: String = match &options.repo_links {
+            Some(links) => format!("[{name}]({})", links.url(&entry.path, entry.is_dir)),
+            None => name,
+        };
+        let name: String = if options.emoji { format!("{} {name}", icons::emoji(&entry.name, entry.is_dir)) } else { name };
+        let mut line: String = format!("{}- {name}", "  ".repeat(depth));
+        for note in entry.notes.iter().chain(&annotations(entry, options)).filter(|note| !note.is_empty()) {
+            line.push(' ');
+            line.push_str(&printable(note, options));
+        }
+        lines.push(line);
+        render_md(&entry.children, depth + 1, options, lines);
+    }
+}
+
+/// Names in `+...+` literal monospace so nothing in them is read as markup, nested by the number of stars
+fn render_asciidoc(entries: &[Entry], depth: usize, options: &RenderOptions, lines: &mut Vec<String>) {
+    for entry in entries {
+        let mut line: String = format!("{} `+{}+`", "*".repeat(depth), shown_name(entry, options));
+        for note in entry.notes.iter().chain(&annotations(entry, options)).filter(|note| !note.is_empty()) {
+            line.push(' ');
+            line.push_str(&printable(note, options));
+        }
+        lines.push(line);
+        render_asciidoc(&entry.children, depth + 1, options, lines);
+    }
+}
+
+/// Headlines with as many stars as the depth, and a `:SIZE:` property with `--size`
+fn render_org(entries: &[Entry], depth: usize, options: &RenderOptions, headline: &RenderOptions, lines: &mut Vec<String>) {
+    for entry in entries {
+        let mut line: String = format!("{} {}", "*".repeat(depth), org_verbatim(&shown_name(entry, options)));
+        for note in entry.notes.iter().chain(&annotations(entry, headline)).filter(|note| !note.is_empty()) {
+            line.push(' ');
+            line.push_str(&printable(note, options));
+        }
+        lines.push(line);
+        if options.size {
+            lines.extend([String::from(":PROPERTIES:"), format!(":SIZE: {}", size::human(entry.size)), String::from(":END:")]);
+        }
+        render_org(&entry.children, depth + 1, options, headline, lines);
+    }
+}
+
+/// `=name=` so underscores and slashes in names aren't read as emphasis, unless the name has an `=` of its own
+fn org_verbatim(name: &str) -> String {
+    if name.contains('=') { name.to_owned() } else { format!("={name}=") }
+}
+
+/// `file://` URL with everything but unreserved characters and separators percent encoded
+fn file_url(path: &std::path::Path) -> String {
+    let path: String = path.to_string_lossy().replace('\\', "/");
+    let mut url: String = String::from(if path.starts_with('/') { "file://" } else { "file:///" });
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => url.push(byte as char),
+            _ => url.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    url
+}
+
+fn json_array(entries: &[Entry], options: &RenderOptions) -> String {
+    let objects: Vec<String> = entries.iter().map(|entry| json_object(entry, options)).collect();
+    format!("[{}]", objects.join(","))
+}
+
+/// Sizes are plain byte counts, the other annotations go with the notes
+fn json_object(entry: &Entry, options: &RenderOptions) -> String {
+    let mut counts: Vec<String> = annotations(entry, options);
+    if options.size {
+        //Size is always the first cell
+        counts.remove(0);
+    }
+    let notes: Vec<String> = entry.notes.iter().chain(&counts).filter(|note| !note.is_empty()).map(|note| json_string(note)).collect();
+    let mut object: String = format!(
+        "{{\"name\":{},\"path\":{},\"type\":\"{}\",\"notes\":[{}]",
+        json_string(&entry.name),
+        json_string(&entry.path),
+        if entry.is_dir { "directory" } else { "file" },
+        notes.join(","),
+    );
+    if options.size {
+        object.push_str(&format!(",\"size\":{}", entry.size));
+    }
+    if entry.is_dir {
+        object.push_str(&format!(",\"children\":{}", json_array(&entry.children, options)));
+    }
+    object.push('}');
+    object
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut quoted: String = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,146 @@
+//! Shell style glob matching on `/` separated relative paths.
+//!
+//! `*` and `?` stay within one path segment, `**` spans any number of segments, `[a-z]`/`[!a-z]`
+//! are character classes and `\` escapes the next character.
+
+/// Whether `pattern` matches all of `path`
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    let mut matcher = Matcher { memo: vec![None; (pattern.len() + 1) * (path.len() + 1)], pattern: &pattern, path: &path };
+    matcher.match_from(0, 0)
+}
+
+/// Matches the pattern from `pattern[p]` against `path[s]`. Every (p, s) pair is only worked out once, otherwise
+/// patterns with several stars (`*a*a*a*b`) take exponential time on paths that almost match
+struct Matcher<'a> {
+    pattern: &'a [char],
+    path: &'a [char],
+    memo: Vec<Option<bool>>,
+}
+
+impl<'a> Matcher<'a> {
+    fn match_from(&mut self, p: usize, s: usize) -> bool {
+        let key: usize = p * (self.path.len() + 1) + s;
+        if let Some(matched) = self.memo[key] {
+            return matched;
+        }
+        let matched: bool = self.step(p, s);
+        self.memo[key] = Some(matched);
+        matched
+    }
+
+    fn step(&mut self, p: usize, s: usize) -> bool {
+        let pattern: &'a [char] = &self.pattern[p..];
+        let path: &'a [char] = &self.path[s..];
+        match pattern {
+            [] => path.is_empty(),
+            ['*', '*', '/', ..] => {
+                //Zero or more whole segments
+                self.match_from(p + 3, s) || (0..path.len()).any(|i| path[i] == '/' && self.match_from(p + 3, s + i + 1))
+            }
+            ['*', '*', ..] => (0..=path.len()).any(|i| self.match_from(p + 2, s + i)),
+            ['*', ..] => {
+                let segment_end: usize = path.iter().position(|&c| c == '/').unwrap_or(path.len());
+                (0..=segment_end).any(|i| self.match_from(p + 1, s + i))
+            }
+            ['?', ..] => path.first().is_some_and(|&c| c != '/') && self.match_from(p + 1, s + 1),
+            ['[', rest @ ..] => match (path.first(), parse_class(rest)) {
+                (Some(&c), Some((matches_class, after))) => {
+                    c != '/' && matches_class(c) && self.match_from(self.pattern.len() - after.len(), s + 1)
+                }
+                //Unclosed bracket is a literal
+                (Some(&c), None) => c == '[' && self.match_from(p + 1, s + 1),
+                (None, _) => false,
+            },
+            ['\\', c, ..] => path.first() == Some(c) && self.match_from(p + 2, s + 1),
+            [c, ..] => path.first() == Some(c) && self.match_from(p + 1, s + 1),
+        }
+    }
+}
+
+/// Parses the inside of `[...]`, returning a matcher and the pattern after the `]`
+fn parse_class(pattern: &[char]) -> Option<(impl Fn(char) -> bool + '_, &[char])> {
+    let (negated, body_start) = match pattern.first() {
+        Some('!' | '^') => (true, 1),
+        _ => (false, 0),
+    };
+    //A `]` right at the start is part of the class
+    let close: usize = body_start + 1 + pattern.get(body_start + 1..)?.iter().position(|&c| c == ']')?;
+    let body: &[char] = &pattern[body_start..close];
+
+    let matcher = move |c: char| {
+        let mut i: usize = 0;
+        let mut found: bool = false;
+        while i < body.len() {
+            if i + 2 < body.len() && body[i + 1] == '-' {
+                found |= body[i] <= c && c <= body[i + 2];
+                i += 3;
+            } else {
+                found |= body[i] == c;
+                i += 1;
+            }
+        }
+        found != negated
+    };
+    Some((matcher, &pattern[close + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn literals_and_wildcards() {
+        assert!(matches("src/main.rs", "src/main.rs"));
+        assert!(!matches("src/main.rs", "src/main.rsx"));
+        assert!(matches("*.rs", "main.rs"));
+        assert!(matches("ma?n.rs", "main.rs"));
+        assert!(!matches("ma?n.rs", "man.rs"));
+    }
+
+    #[test]
+    fn single_star_and_question_mark_stay_in_a_segment() {
+        assert!(!matches("*.rs", "src/main.rs"));
+        assert!(matches("src/*", "src/main.rs"));
+        assert!(!matches("src/*", "src/bin/tool.rs"));
+        assert!(!matches("src?main.rs", "src/main.rs"));
+    }
+
+    #[test]
+    fn double_star_spans_segments() {
+        assert!(matches("**/*.rs", "main.rs"));
+        assert!(matches("**/*.rs", "src/bin/tool.rs"));
+        assert!(matches("src/**", "src/bin/tool.rs"));
+        assert!(matches("src/**/tool.rs", "src/tool.rs"));
+        assert!(matches("src/**/tool.rs", "src/a/b/tool.rs"));
+        assert!(!matches("src/**/tool.rs", "lib/a/tool.rs"));
+        assert!(!matches("src/**/tool.rs", "src/atool.rs"));
+        assert!(matches("a**b", "a/x/b"));
+        assert!(matches("**", ""));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert!(matches("[a-c].txt", "b.txt"));
+        assert!(!matches("[a-c].txt", "d.txt"));
+        assert!(matches("[!a-c].txt", "d.txt"));
+        assert!(matches("[^a-c].txt", "d.txt"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[a-", "[a-"));
+        assert!(!matches("[/]", "/"));
+    }
+
+    #[test]
+    fn escapes() {
+        assert!(matches("\\*.rs", "*.rs"));
+        assert!(!matches("\\*.rs", "main.rs"));
+    }
+
+    #[test]
+    fn many_stars_on_a_near_miss_finish() {
+        let path: String = "a/".repeat(40) + &"a".repeat(40);
+        assert!(!matches("**/**/**/**/**/**/b", &path));
+        assert!(!matches("*a*a*a*a*a*a*a*a*a*b", &"a".repeat(60)));
+    }
+}
@@ -0,0 +1,97 @@
+//! Emoji markers shown before names with `--emoji`, and Nerd Font glyphs with `--icons`.
+
+use clap::ValueEnum;
+
+/// By file name, checked before the extension. Emoji, then the Nerd Font glyph
+const BY_NAME: &[(&str, &str, char)] = &[
+    ("Dockerfile", "🐳", '\u{f308}'),
+    ("docker-compose.yml", "🐳", '\u{f308}'),
+    ("Makefile", "🛠️", '\u{f489}'),
+    ("LICENSE", "⚖️", '\u{f02d}'),
+    ("LICENCE", "⚖️", '\u{f02d}'),
+    (".gitignore", "🙈", '\u{f1d3}'),
+    ("Cargo.lock", "🔒", '\u{e7a8}'),
+    ("package-lock.json", "🔒", '\u{e71e}'),
+    ("yarn.lock", "🔒", '\u{f023}'),
+];
+
+const BY_EXTENSION: &[(&[&str], &str, char)] = &[
+    (&["rs"], "🦀", '\u{e7a8}'),
+    (&["py", "pyi", "ipynb"], "🐍", '\u{e606}'),
+    (&["js", "mjs", "cjs", "jsx"], "📜", '\u{e74e}'),
+    (&["ts", "tsx"], "📜", '\u{e628}'),
+    (&["go"], "🐹", '\u{e627}'),
+    (&["rb"], "💎", '\u{e21e}'),
+    (&["java", "kt", "kts"], "☕", '\u{e256}'),
+    (&["sh", "bash", "zsh", "fish", "ps1"], "🐚", '\u{f489}'),
+    (&["html", "htm"], "🌐", '\u{f13b}'),
+    (&["css", "scss", "less"], "🎨", '\u{e749}'),
+    (&["sql", "db", "sqlite"], "🗃️", '\u{f1c0}'),
+    (&["md", "markdown", "txt", "rst", "adoc"], "📝", '\u{f48a}'),
+    (&["pdf"], "📕", '\u{f1c1}'),
+    (&["toml", "yaml", "yml", "json", "ini", "cfg", "conf", "env", "xml"], "⚙️", '\u{e615}'),
+    (&["lock"], "🔒", '\u{f023}'),
+    (&["png", "jpg", "jpeg", "gif", "svg", "webp", "ico", "bmp", "tiff"], "🖼️", '\u{f1c5}'),
+    (&["mp3", "wav", "flac", "ogg", "m4a"], "🎵", '\u{f001}'),
+    (&["mp4", "mkv", "mov", "avi", "webm"], "🎬", '\u{f03d}'),
+    (&["zip", "tar", "gz", "tgz", "xz", "bz2", "7z", "rar", "zst"], "📦", '\u{f410}'),
+    (&["ttf", "otf", "woff", "woff2"], "🔤", '\u{f031}'),
+    (&["exe", "dll", "so", "dylib", "wasm", "bin"], "⚡", '\u{f471}'),
+];
+
+/// Emoji for an entry, 📁 for directories and 📄 for anything unrecognized
+pub fn emoji(name: &str, is_dir: bool) -> &'static str {
+    if is_dir {
+        return "📁";
+    }
+    if name.starts_with("README") {
+        return "📖";
+    }
+    lookup(name).map_or("📄", |(emoji, _)| emoji)
+}
+
+/// Nerd Font glyph for an entry, like lsd and eza show, with a folder and a plain file for the rest
+pub fn glyph(name: &str, is_dir: bool) -> char {
+    if is_dir {
+        return '\u{f07b}';
+    }
+    if name.starts_with("README") {
+        return '\u{f02d}';
+    }
+    lookup(name).map_or('\u{f15b}', |(_, glyph)| glyph)
+}
+
+fn lookup(name: &str) -> Option<(&'static str, char)> {
+    if let Some(&(_, emoji, glyph)) = BY_NAME.iter().find(|(file_name, _, _)| *file_name == name) {
+        return Some((emoji, glyph));
+    }
+    let extension: String = name.rsplit_once('.').map(|(_, extension)| extension.to_ascii_lowercase()).unwrap_or_default();
+    BY_EXTENSION.iter()
+        .find(|(extensions, _, _)| extensions.contains(&extension.as_str()))
+        .map(|&(_, emoji, glyph)| (emoji, glyph))
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Icons {
+    /// On terminals that can have a Nerd Font
+    Auto,
+    Always,
+    Never,
+}
+
+impl Icons {
+    pub fn enabled(self) -> bool {
+        match self {
+            Icons::Auto => terminal_can_show_glyphs(),
+            Icons::Always => true,
+            Icons::Never => false,
+        }
+    }
+}
+
+/// Which font a terminal uses can't be queried. The Linux console and dumb terminals can't have one with the glyphs,
+/// NERD_FONT=0 rules out the rest
+fn terminal_can_show_glyphs() -> bool {
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    !["linux", "dumb"].contains(&var("TERM").as_str()) && var("NERD_FONT") != "0"
+}
@@ -0,0 +1,157 @@
+//! Ignore files (`.dockerignore`, `.npmignore`) deciding which entries are left out of the tree, and the root
+//! `.gitignore` for telling ignored entries apart.
+
+use crate::glob;
+use crate::json::Json;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Flavor {
+    /// Patterns are relative to the root, `!` can re-include files inside excluded directories
+    Docker,
+    /// Patterns without a `/` match at any depth, nothing inside an excluded directory comes back
+    Git,
+}
+
+struct Rule {
+    pattern: String,
+    /// `!pattern`, re-includes what earlier rules excluded
+    negated: bool,
+    /// `pattern/`, only matches directories
+    dir_only: bool,
+}
+
+/// Files npm always packs, whatever the ignore rules or "files" say
+const NPM_ALWAYS_INCLUDED: &[&str] = &["package.json", "README*", "readme*", "Readme*", "LICENSE*", "LICENCE*", "license*", "licence*"];
+
+/// Files npm never packs
+const NPM_ALWAYS_EXCLUDED: &[&str] = &[
+    ".git", "CVS", ".svn", ".hg", ".lock-wscript", ".wafpickle-*", ".*.swp", ".DS_Store", "._*",
+    "npm-debug.log", ".npmrc", "node_modules", "config.gypi", "*.orig", "package-lock.json", "yarn.lock", "pnpm-lock.yaml",
+];
+
+pub struct IgnoreRules {
+    flavor: Flavor,
+    rules: Vec<Rule>,
+    /// Root relative patterns that everything must be under, like npm's "files"
+    only: Option<Vec<String>>,
+    /// Root relative patterns that are never excluded
+    always: Vec<String>,
+}
+
+impl IgnoreRules {
+    /// Reads a `.dockerignore`. Patterns are relative to the root, a pattern matching a directory excludes everything in it,
+    /// and the last matching rule wins
+    pub fn dockerignore(file: &Path) -> io::Result<IgnoreRules> {
+        Ok(IgnoreRules { flavor: Flavor::Docker, rules: parse_rules(&fs::read_to_string(file)?), only: None, always: Vec::new() })
+    }
+
+    /// The root `.gitignore`, nothing is ignored without one
+    pub fn gitignore(root: &Path) -> IgnoreRules {
+        let rules: Vec<Rule> = parse_rules(&fs::read_to_string(root.join(".gitignore")).unwrap_or_default());
+        IgnoreRules { flavor: Flavor::Git, rules, only: None, always: Vec::new() }
+    }
+
+    /// What `npm pack` in `root` would include: the package.json "files" whitelist, or else `.npmignore`
+    /// (`.gitignore` if there is none), plus the files npm always includes or excludes
+    pub fn npm_pack(root: &Path) -> io::Result<IgnoreRules> {
+        let manifest: Json = Json::parse(&fs::read_to_string(root.join("package.json"))?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "package.json is not valid JSON"))?;
+
+        let only: Option<Vec<String>> = manifest.get("files").and_then(Json::as_array).map(|files| {
+            files.iter().filter_map(Json::as_str).map(clean).filter(|pattern| !pattern.is_empty()).collect()
+        });
+
+        //The root ignore file doesn't apply when there is a "files" list
+        let mut rules: Vec<Rule> = Vec::new();
+        if only.is_none() {
+            let ignore_file: String = fs::read_to_string(root.join(".npmignore"))
+                .or_else(|_| fs::read_to_string(root.join(".gitignore")))
+                .unwrap_or_default();
+            rules = parse_rules(&ignore_file);
+        }
+        rules.extend(NPM_ALWAYS_EXCLUDED.iter().map(|&pattern| Rule { pattern: pattern.to_owned(), negated: false, dir_only: false }));
+
+        let mut always: Vec<String> = NPM_ALWAYS_INCLUDED.iter().map(|&pattern| pattern.to_owned()).collect();
+        always.extend(manifest.get("main").and_then(Json::as_str).map(clean));
+
+        Ok(IgnoreRules { flavor: Flavor::Git, rules, only, always })
+    }
+
+    /// Whether the root relative, `/` separated `path` is excluded
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        if self.always.iter().any(|pattern| glob::matches(pattern, path)) {
+            return false;
+        }
+        if let Some(only) = &self.only {
+            let is_listed = |pattern: &String| glob::matches(pattern, path) || parents(path).any(|parent| glob::matches(pattern, parent));
+            if !only.iter().any(is_listed) {
+                return true;
+            }
+        }
+
+        match self.flavor {
+            Flavor::Docker => {
+                let mut ignored: bool = false;
+                for rule in &self.rules {
+                    if ignored != rule.negated {
+                        continue;
+                    }
+                    if glob::matches(&rule.pattern, path) || parents(path).any(|parent| glob::matches(&rule.pattern, parent)) {
+                        ignored = !rule.negated;
+                    }
+                }
+                ignored
+            }
+            Flavor::Git => parents(path).any(|parent| self.git_ignored(parent, true)) || self.git_ignored(path, is_dir),
+        }
+    }
+
+    /// Last matching gitignore rule for `path` itself
+    fn git_ignored(&self, path: &str, is_dir: bool) -> bool {
+        let name: &str = path.rsplit('/').next().unwrap_or(path);
+        let mut ignored: bool = false;
+        for rule in self.rules.iter().filter(|rule| is_dir || !rule.dir_only) {
+            //Patterns without a slash match the name at any depth
+            let is_match: bool = if rule.pattern.contains('/') { glob::matches(&rule.pattern, path) } else { glob::matches(&rule.pattern, name) };
+            if is_match {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+
+    /// Whether an excluded directory might still have included entries inside, so it has to be scanned anyway
+    pub fn has_exceptions(&self) -> bool {
+        self.only.is_some() || (self.flavor == Flavor::Docker && self.rules.iter().any(|rule| rule.negated))
+    }
+}
+
+fn parse_rules(text: &str) -> Vec<Rule> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (negated, pattern) = match line.strip_prefix('!') {
+                Some(pattern) => (true, pattern.trim()),
+                None => (false, line),
+            };
+            let dir_only: bool = pattern.ends_with('/');
+            let pattern: String = clean(pattern);
+            (!pattern.is_empty()).then_some(Rule { pattern, negated, dir_only })
+        })
+        .collect()
+}
+
+/// Like Go's filepath.Clean, which docker runs patterns through: no leading `/` or `./`, no empty or `.` segments
+fn clean(pattern: &str) -> String {
+    let segments: Vec<&str> = pattern.split('/').filter(|segment| !segment.is_empty() && *segment != ".").collect();
+    segments.join("/")
+}
+
+/// `a/b/c` -> `a`, `a/b`
+fn parents(path: &str) -> impl Iterator<Item = &str> {
+    path.match_indices('/').map(move |(i, _)| &path[..i])
+}
@@ -0,0 +1,50 @@
+//! Ctrl-C handling. The first one stops the scan so whatever was gathered still gets printed, written and copied,
+//! a second one exits right away.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Exit code for being stopped by SIGINT, like shells report
+pub const EXIT_CODE: i32 = 130;
+
+pub fn requested() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+fn on_interrupt() {
+    if INTERRUPTED.swap(true, Ordering::Relaxed) {
+        std::process::exit(EXIT_CODE);
+    }
+}
+
+#[cfg(unix)]
+pub fn install() {
+    extern "C" fn handler(_signal: libc::c_int) {
+        on_interrupt();
+    }
+    //SAFETY: the handler only touches an atomic, or exits on the second signal
+    unsafe {
+        libc::signal(libc::SIGINT, handler as extern "C" fn(libc::c_int) as libc::sighandler_t);
+    }
+}
+
+#[cfg(windows)]
+pub fn install() {
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: Option<unsafe extern "system" fn(u32) -> i32>, add: i32) -> i32;
+    }
+    unsafe extern "system" fn handler(event: u32) -> i32 {
+        //CTRL_C_EVENT, leave the others (closing the console, logoff) to the default handler
+        if event != 0 { return 0; }
+        on_interrupt();
+        1
+    }
+    //SAFETY: kernel32 is always linked, and the handler only touches an atomic
+    unsafe {
+        SetConsoleCtrlHandler(Some(handler), 1);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn install() {}
@@ -0,0 +1,267 @@
+//! Just enough JSON to read manifests like package.json and API responses.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a whole document, `None` if it isn't valid JSON
+    pub fn parse(text: &str) -> Option<Json> {
+        let mut parser = Parser { chars: text.chars().collect(), pos: 0 };
+        let value: Json = parser.value()?;
+        parser.skip_whitespace();
+        (parser.pos == parser.chars.len()).then_some(value)
+    }
+
+    /// Member `key` of an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Json::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c: Option<char> = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, word: &str) -> Option<()> {
+        word.chars().all(|c| self.next() == Some(c)).then_some(())
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' => self.object(),
+            '[' => self.array(),
+            '"' => self.string().map(Json::String),
+            't' => self.expect("true").map(|_| Json::Bool(true)),
+            'f' => self.expect("false").map(|_| Json::Bool(false)),
+            'n' => self.expect("null").map(|_| Json::Null),
+            _ => self.number(),
+        }
+    }
+
+    fn object(&mut self) -> Option<Json> {
+        self.next();
+        let mut members: Vec<(String, Json)> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Some(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key: String = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                '}' => return Some(Json::Object(members)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<Json> {
+        self.next();
+        let mut items: Vec<Json> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Some(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                ']' => return Some(Json::Array(items)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.next()? != '"' {
+            return None;
+        }
+        let mut string: String = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Some(string),
+                '\\' => match self.next()? {
+                    'n' => string.push('\n'),
+                    't' => string.push('\t'),
+                    'r' => string.push('\r'),
+                    'b' => string.push('\u{8}'),
+                    'f' => string.push('\u{c}'),
+                    c @ ('"' | '\\' | '/') => string.push(c),
+                    'u' => {
+                        let unit: u32 = self.hex4()?;
+                        //Characters outside the BMP come as a surrogate pair, a lone half is a replacement character
+                        let code: u32 = if (0xD800..0xDC00).contains(&unit) && self.chars[self.pos..].starts_with(&['\\', 'u']) {
+                            let rewind: usize = self.pos;
+                            self.pos += 2;
+                            match self.hex4()? {
+                                low @ 0xDC00..=0xDFFF => 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00),
+                                _ => {
+                                    self.pos = rewind;
+                                    unit
+                                }
+                            }
+                        } else {
+                            unit
+                        };
+                        string.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    }
+                    _ => return None,
+                },
+                //Control characters have to be escaped
+                c if c < ' ' => return None,
+                c => string.push(c),
+            }
+        }
+    }
+
+    /// Four hex digits of a `\u` escape
+    fn hex4(&mut self) -> Option<u32> {
+        let hex: String = (0..4).map(|_| self.next()).collect::<Option<String>>()?;
+        if hex.chars().all(|c| c.is_ascii_hexdigit()) { u32::from_str_radix(&hex, 16).ok() } else { None }
+    }
+
+    /// `-?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?`, stricter than what Rust parses (`+1`, `.5`, `1.`, `01`, `inf`)
+    fn number(&mut self) -> Option<Json> {
+        let start: usize = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        match self.peek()? {
+            '0' => self.pos += 1,
+            '1'..='9' => self.digits(),
+            _ => return None,
+        }
+        if self.peek() == Some('.') {
+            self.pos += 1;
+            self.require_digits()?;
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.pos += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.pos += 1;
+            }
+            self.require_digits()?;
+        }
+        let number: String = self.chars[start..self.pos].iter().collect();
+        number.parse().ok().map(Json::Number)
+    }
+
+    fn digits(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+    }
+
+    fn require_digits(&mut self) -> Option<()> {
+        let start: usize = self.pos;
+        self.digits();
+        (self.pos > start).then_some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+
+    fn string(text: &str) -> Option<Json> {
+        Some(Json::String(String::from(text)))
+    }
+
+    #[test]
+    fn documents() {
+        let json: Json = Json::parse(r#" { "name": "demo", "tree": [ { "path": "a", "size": 12 }, null, true ], "empty": {} } "#).unwrap();
+        assert_eq!(json.get("name").and_then(Json::as_str), Some("demo"));
+        let tree: &[Json] = json.get("tree").and_then(Json::as_array).unwrap();
+        assert_eq!(tree[0].get("size").and_then(Json::as_number), Some(12.0));
+        assert_eq!(tree[1..], [Json::Null, Json::Bool(true)]);
+        assert_eq!(json.get("empty"), Some(&Json::Object(Vec::new())));
+        assert_eq!(json.get("missing"), None);
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Json::parse("0"), Some(Json::Number(0.0)));
+        assert_eq!(Json::parse("-12.5e2"), Some(Json::Number(-1250.0)));
+        assert_eq!(Json::parse("1E-2"), Some(Json::Number(0.01)));
+        for text in ["+1", "01", ".5", "1.", "1e", "-", "--1", "1e+", "inf", "NaN", "0x10"] {
+            assert_eq!(Json::parse(text), None, "{text} should be rejected");
+        }
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(Json::parse(r#""a\"b\\c\/d\n""#), string("a\"b\\c/d\n"));
+        assert_eq!(Json::parse(r#""\u00e9 é""#), string("é é"));
+        assert_eq!(Json::parse(r#""\ud83d\ude00""#), string("😀"));
+        assert_eq!(Json::parse(r#""\ud83d!""#), string("\u{fffd}!"));
+        assert_eq!(Json::parse(r#""\q""#), None);
+        assert_eq!(Json::parse(r#""\u12g4""#), None);
+        assert_eq!(Json::parse("\"tab\there\""), None);
+        assert_eq!(Json::parse("\"unterminated"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        for text in ["", "[1,]", "{\"a\":1,}", "{a:1}", "[1 2]", "{\"a\" 1}", "tru", "nul", "[1] x", "\u{a0}1"] {
+            assert_eq!(Json::parse(text), None, "{text:?} should be rejected");
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Trees that come as a flat list of paths (a remote API, stdin) instead of from walking the filesystem.
+
+use std::collections::BTreeMap;
+
+/// A listed file or directory, `path` being `/` separated from the root
+pub struct Listed {
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// Listed entries nested by path. Directories only implied by a path inside them are filled in
+#[derive(Default)]
+pub struct Node {
+    pub is_dir: bool,
+    pub size: u64,
+    pub children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    /// Size of a file, or of everything listed below a directory
+    pub fn total_size(&self) -> u64 {
+        if self.is_dir { self.children.values().map(Node::total_size).sum() } else { self.size }
+    }
+}
+
+/// One path per line, like `fd`, `rg -l` or `git diff --name-only` print them. A trailing `/` marks a directory
+pub fn parse(text: &str) -> Vec<Listed> {
+    text.lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Listed {
+            //`./src/main.rs` and `src/main.rs` are the same entry
+            path: line.split('/').filter(|segment| *segment != ".").collect::<Vec<&str>>().join("/"),
+            is_dir: line.ends_with('/'),
+            size: 0,
+        })
+        .collect()
+}
+
+pub fn nest(listed: Vec<Listed>) -> BTreeMap<String, Node> {
+    let mut root: BTreeMap<String, Node> = BTreeMap::new();
+    for item in listed {
+        let mut level: &mut BTreeMap<String, Node> = &mut root;
+        let mut segments = item.path.split('/').filter(|segment| !segment.is_empty()).peekable();
+        while let Some(segment) = segments.next() {
+            let node: &mut Node = level.entry(segment.to_owned()).or_default();
+            if segments.peek().is_some() {
+                node.is_dir = true;
+            } else {
+                node.is_dir |= item.is_dir;
+                node.size = item.size;
+            }
+            level = &mut node.children;
+        }
+    }
+    root
+}
@@ -0,0 +1,137 @@
+//! Lightweight line counting (code / comment / blank), tokei-style but without parsing strings.
+
+use crate::content;
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+use std::path::Path;
+
+struct Language {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+}
+
+const C_BLOCK: Option<(&str, &str)> = Some(("/*", "*/"));
+
+const LANGUAGES: &[Language] = &[
+    Language { name: "Rust", extensions: &["rs"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "C", extensions: &["c", "h"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "C++", extensions: &["cpp", "cc", "cxx", "hpp", "hh"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "C#", extensions: &["cs"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "Go", extensions: &["go"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "Java", extensions: &["java"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "Kotlin", extensions: &["kt", "kts"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "Swift", extensions: &["swift"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "JavaScript", extensions: &["js", "jsx", "mjs", "cjs"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "TypeScript", extensions: &["ts", "tsx", "mts", "cts"], line_comments: &["//"], block_comment: C_BLOCK },
+    Language { name: "CSS", extensions: &["css", "scss", "less"], line_comments: &[], block_comment: C_BLOCK },
+    Language { name: "HTML", extensions: &["html", "htm", "vue", "svelte"], line_comments: &[], block_comment: Some(("<!--", "-->")) },
+    Language { name: "Python", extensions: &["py", "pyi"], line_comments: &["#"], block_comment: None },
+    Language { name: "Ruby", extensions: &["rb"], line_comments: &["#"], block_comment: None },
+    Language { name: "Shell", extensions: &["sh", "bash", "zsh", "fish"], line_comments: &["#"], block_comment: None },
+    Language { name: "Lua", extensions: &["lua"], line_comments: &["--"], block_comment: Some(("--[[", "]]")) },
+    Language { name: "SQL", extensions: &["sql"], line_comments: &["--"], block_comment: C_BLOCK },
+    Language { name: "Haskell", extensions: &["hs"], line_comments: &["--"], block_comment: Some(("{-", "-}")) },
+    Language { name: "TOML", extensions: &["toml"], line_comments: &["#"], block_comment: None },
+    Language { name: "YAML", extensions: &["yml", "yaml"], line_comments: &["#"], block_comment: None },
+    Language { name: "JSON", extensions: &["json"], line_comments: &[], block_comment: None },
+    Language { name: "Markdown", extensions: &["md", "markdown"], line_comments: &[], block_comment: Some(("<!--", "-->")) },
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LocCounts {
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+}
+
+impl LocCounts {
+    pub fn is_empty(&self) -> bool {
+        self.code + self.comment + self.blank == 0
+    }
+}
+
+impl AddAssign for LocCounts {
+    fn add_assign(&mut self, other: LocCounts) {
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+    }
+}
+
+impl std::fmt::Display for LocCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "[{} code, {} comment, {} blank]", self.code, self.comment, self.blank)
+    }
+}
+
+/// Counts the lines of a source file in a known language, returning the language name with the counts
+pub fn count_file(path: &Path) -> Option<(&'static str, LocCounts)> {
+    let extension: String = path.extension()?.to_str()?.to_ascii_lowercase();
+    let language: &Language = LANGUAGES.iter().find(|language| language.extensions.contains(&extension.as_str()))?;
+    let text: String = content::read_text(path)?;
+    Some((language.name, count_lines(language, &text)))
+}
+
+fn count_lines(language: &Language, text: &str) -> LocCounts {
+    let mut counts: LocCounts = LocCounts::default();
+    //Closing token of the block comment we're inside of
+    let mut in_block: Option<&str> = None;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(end) = in_block {
+            counts.comment += 1;
+            if line.contains(end) { in_block = None; }
+        } else if line.is_empty() {
+            counts.blank += 1;
+        } else if language.line_comments.iter().any(|token| line.starts_with(token)) {
+            counts.comment += 1;
+        } else if let Some((start, end)) = language.block_comment.filter(|(start, _)| line.starts_with(start)) {
+            counts.comment += 1;
+            if !line[start.len()..].contains(end) { in_block = Some(end); }
+        } else {
+            counts.code += 1;
+            //Code followed by an unterminated block comment
+            if let Some((start, end)) = language.block_comment {
+                if let Some(i) = line.find(start) {
+                    if !line[i + start.len()..].contains(end) { in_block = Some(end); }
+                }
+            }
+        }
+    }
+    counts
+}
+
+/// Per language totals for the summary table
+#[derive(Default)]
+pub struct LocSummary(BTreeMap<&'static str, (usize, LocCounts)>);
+
+impl LocSummary {
+    pub fn add(&mut self, language: &'static str, counts: LocCounts) {
+        let (files, total) = self.0.entry(language).or_default();
+        *files += 1;
+        *total += counts;
+    }
+
+    /// Renders the table, one row per language plus a total
+    pub fn table(&self) -> String {
+        let mut rows: Vec<(String, usize, LocCounts)> = self.0.iter()
+            .map(|(&language, &(files, counts))| (language.to_owned(), files, counts))
+            .collect();
+        rows.sort_by_key(|(_, _, counts)| std::cmp::Reverse(counts.code));
+
+        let mut total: (usize, LocCounts) = (0, LocCounts::default());
+        for (_, files, counts) in &rows {
+            total.0 += files;
+            total.1 += *counts;
+        }
+        rows.push((String::from("Total"), total.0, total.1));
+
+        let mut table: String = format!("{:<12} {:>7} {:>9} {:>9} {:>9}", "Language", "Files", "Code", "Comment", "Blank");
+        for (language, files, counts) in rows {
+            table.push_str(&format!("\n{language:<12} {files:>7} {:>9} {:>9} {:>9}", counts.code, counts.comment, counts.blank));
+        }
+        table
+    }
+}
@@ -0,0 +1,68 @@
+//! `LS_COLORS`, the palette `ls` takes from `dircolors`, so entries look the same as in the user's `ls`.
+//!
+//! It's a `:` separated list of `key=SGR codes`, the keys being two letter entry types (`di` directories, `ex`
+//! executables, ...) and `*suffix` patterns for file names (`*.tar`, `*README`).
+
+use crate::class::Class;
+use crate::style::Style;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Used when LS_COLORS isn't set: directories blue, links cyan (broken ones red), executables green, archives red
+const BUILT_IN: &str = "di=34:ln=36:or=31:ex=32:pi=33:so=35:bd=33:cd=33:\
+    *.tar=31:*.tgz=31:*.gz=31:*.zip=31:*.xz=31:*.bz2=31:*.7z=31:*.rar=31:*.zst=31:*.jar=31:*.deb=31:*.rpm=31";
+
+pub struct LsColors {
+    /// By two letter type
+    types: HashMap<String, Style>,
+    /// Lowercased suffixes from the `*` patterns, the longest first
+    suffixes: Vec<(String, Style)>,
+    /// `ln=target`, links get the color of what they point to
+    links_as_target: bool,
+}
+
+impl LsColors {
+    /// From the environment, or the built-in palette if it isn't set
+    pub fn from_env() -> LsColors {
+        std::env::var("LS_COLORS").ok().filter(|spec| !spec.is_empty()).map_or_else(|| LsColors::parse(BUILT_IN), |spec| LsColors::parse(&spec))
+    }
+
+    /// Entries that don't parse are skipped, like `ls` does
+    pub fn parse(spec: &str) -> LsColors {
+        let mut colors = LsColors { types: HashMap::new(), suffixes: Vec::new(), links_as_target: false };
+        for (key, codes) in spec.split(':').filter_map(|entry| entry.split_once('=')) {
+            if key == "ln" && codes == "target" {
+                colors.links_as_target = true;
+            } else if let Some(suffix) = key.strip_prefix('*') {
+                colors.suffixes.push((suffix.to_lowercase(), Style::from_sgr(codes)));
+            } else {
+                colors.types.insert(key.to_owned(), Style::from_sgr(codes));
+            }
+        }
+        //Stable, so of equally long suffixes the first one wins
+        colors.suffixes.sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+        colors
+    }
+
+    /// The style `ls` would give the entry at `path`. Types other than plain files win over the name, like in `ls`
+    pub fn style(&self, path: &Path, class: Class) -> Option<Style> {
+        let class: Class = if class == Class::Link && self.links_as_target { Class::of_target(path) } else { class };
+        let by_type = |key: &str| self.types.get(key).copied();
+        match class {
+            Class::File => self.by_name(path).or_else(|| by_type("fi")),
+            Class::Executable => by_type("ex").or_else(|| self.by_name(path)),
+            Class::Dir => by_type("di"),
+            Class::Link => by_type("ln"),
+            Class::BrokenLink => by_type("or").or_else(|| by_type("ln")),
+            Class::Fifo => by_type("pi"),
+            Class::Socket => by_type("so"),
+            Class::BlockDevice => by_type("bd"),
+            Class::CharDevice => by_type("cd"),
+        }
+    }
+
+    fn by_name(&self, path: &Path) -> Option<Style> {
+        let name: String = path.file_name()?.to_string_lossy().to_lowercase();
+        self.suffixes.iter().find(|(suffix, _)| name.ends_with(suffix.as_str())).map(|&(_, style)| style)
+    }
+}
@@ -6,18 +6,79 @@
 //! Make ignore / stop check more elegant, is HashMap<PathBuf> really the best way to do this?
 //! 
 
-use clap::Parser;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use clap::{Parser, Subcommand};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::fs;
-use std::io;
-use clipboard::{ClipboardContext, ClipboardProvider};
+use std::io::{self, IsTerminal, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
+mod builder;
+mod cache;
+mod class;
+mod clip;
+mod collate;
+mod compat;
+mod config;
+mod content;
+mod depth;
+mod diagram;
+mod diff;
+mod dump;
+mod ecosystem;
+mod entry;
+mod exec;
+mod format;
+mod glob;
+mod icons;
+mod ignore;
+mod interrupt;
+mod json;
+mod listing;
+mod loc;
+mod ls_colors;
+mod manifest;
+mod ncdu;
+mod output;
+mod picker;
+mod platform;
+mod remote;
+mod repo;
+mod scaffold;
+mod script;
+mod serve;
+mod size;
+mod sort;
+mod stats;
+mod style;
+mod template;
+mod timing;
+mod toml;
+mod treemap;
+mod tui;
+mod verify;
+
+use cache::Child;
+use class::Class;
+use collate::Collation;
+use config::Config;
+use content::TodoCounts;
+use entry::Entry;
+use format::{Format, RenderOptions};
+use ignore::IgnoreRules;
+use loc::{LocCounts, LocSummary};
+use platform::FileId;
+use style::Style;
 
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Files to ignore in the tree
     #[arg(short, long, value_name = "FILE")]
     ignore: Vec<String>,
@@ -26,9 +87,45 @@ struct Args {
     #[arg(short, long, value_name = "FILE")]
     stop: Vec<String>,
 
-    /// Output file
-    #[arg(short, long, value_name = "FILE")]
-    output: Option<String>,
+    /// Ignore entries whose names match PATTERN, with | between alternatives like tree -I. Can be repeated
+    #[arg(short = 'I', long, value_name = "PATTERN", value_delimiter = '|')]
+    ignore_pattern: Vec<String>,
+
+    /// Only show files whose names match PATTERN (directories are all kept), with | between alternatives like tree -P
+    #[arg(short = 'P', long, value_name = "PATTERN", value_delimiter = '|')]
+    pattern: Vec<String>,
+
+    /// Format printed and copied, and written to output files without a :FORMAT
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+
+    /// Output exactly like GNU tree's -J or -X, for scripts that parse it (same as --format tree-json/tree-xml)
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with = "format")]
+    compat: Option<compat::Compat>,
+
+    /// Read -L as --depth, -d as --dirs-only and -l as --follow-symlinks, like GNU tree
+    #[arg(long)]
+    gnu: bool,
+
+    /// Link entries in markdown output to the repository's web UI (origin remote, current branch)
+    #[arg(long)]
+    repo_links: bool,
+
+    /// Output file, optionally with a format (any of --format) after a colon. Can be repeated
+    #[arg(short, long, value_name = "FILE[:FORMAT]", value_parser = output::parse_target)]
+    output: Vec<output::Target>,
+
+    /// Leave out what .dockerignore excludes, previewing the docker build context
+    #[arg(long)]
+    dockerignore: bool,
+
+    /// Only show what `npm pack` would include ("files" in package.json, .npmignore)
+    #[arg(long, conflicts_with = "dockerignore")]
+    npm_pack: bool,
+
+    /// Don't stop at build/cache directories of the detected ecosystems (target, dist, __pycache__, ...)
+    #[arg(long)]
+    no_auto_stop: bool,
 
     /// Show node_modules
     #[arg(long)]
@@ -42,79 +139,842 @@ struct Args {
     #[arg(long)]
     vscode: bool,
 
+    /// Show everything hidden by default (.git, .vscode and the config's default_ignore)
+    #[arg(short, long)]
+    all: bool,
+
     /// Include root
     #[arg(short, long)]
     root: bool,
 
+    /// Only show LEVELS levels below the root
+    #[arg(long, value_name = "LEVELS")]
+    depth: Option<usize>,
+
+    /// Show LEVELS levels below the directories matching PATH instead, e.g. node_modules=1 or src=99. Can be repeated
+    #[arg(long, value_name = "PATH=LEVELS", value_parser = depth::parse_override)]
+    depth_for: Vec<depth::Override>,
+
     /// Prioritize directories
+    #[arg(short, long, visible_alias = "dirsfirst")]
+    dirs: bool,
+
+    /// Only show directories
+    #[arg(long)]
+    dirs_only: bool,
+
+    /// Show entries matching PATTERN dimmed, e.g. generated files that are committed but uninteresting. Can be repeated
+    #[arg(long, value_name = "PATTERN")]
+    dim: Vec<String>,
+
+    /// Order of the entries on each level
+    #[arg(long, value_enum, value_name = "ORDER", default_value_t)]
+    sort: sort::SortBy,
+
+    /// How names are ordered and compared
+    #[arg(long, value_enum, value_name = "COLLATION", default_value_t)]
+    collate: Collation,
+
+    /// Print each entry's path from the root instead of its name
     #[arg(short, long)]
-    dirs: bool
+    full_path: bool,
+
+    /// Print each entry's canonical absolute path, for scripts that act on the files
+    #[arg(long, conflicts_with = "repo_links")]
+    absolute: bool,
+
+    /// Put names in double quotes, escaping quotes in them, so names with spaces are unambiguous
+    #[arg(short = 'Q', long)]
+    quote: bool,
+
+    /// Print control characters in names as they are, instead of as \xNN escapes
+    #[arg(short = 'N', long)]
+    literal: bool,
+
+    /// Characters to draw the tree's guides with
+    #[arg(long, value_enum, value_name = "CHARSET", default_value_t)]
+    charset: format::Charset,
+
+    /// Guides for --charset custom, as BRANCH,LAST,PIPE[,BLANK], e.g. '├─ ,└─ ,│  '
+    #[arg(long, value_name = "GLYPHS", value_parser = format::Glyphs::parse, required_if_eq("charset", "custom"))]
+    glyphs: Option<format::Glyphs>,
+
+    /// Indent the tree with spaces only, without any lines
+    #[arg(long, conflicts_with = "glyphs")]
+    no_lines: bool,
+
+    /// Put SUFFIX after directory names instead of /, e.g. \ or none
+    #[arg(long, value_name = "SUFFIX")]
+    dir_suffix: Option<String>,
+
+    /// Lines like `level 2: src/main.rs` instead of the tree guides, for screen readers
+    #[arg(long)]
+    accessible: bool,
+
+    /// Put 📁 before directories and a file type emoji before files
+    #[arg(long)]
+    emoji: bool,
+
+    /// Don't color entries by type (LS_COLORS or the built-in palette), only by the config
+    #[arg(long)]
+    no_type_colors: bool,
+
+    /// Color entries by type with the theme in FILE (a [theme] section) instead of the config's
+    #[arg(long, value_name = "FILE")]
+    theme: Option<PathBuf>,
+
+    /// Put Nerd Font file type glyphs before names in the printed tree (auto: unless the terminal can't have the font)
+    #[arg(long, value_enum, value_name = "WHEN", num_args = 0..=1, default_missing_value = "auto", conflicts_with = "emoji")]
+    icons: Option<icons::Icons>,
+
+    /// Cut lines wider than the terminal short with an ellipsis, keeping the annotations visible
+    #[arg(long)]
+    truncate: bool,
+
+    /// Wrap lines wider than the terminal, continuing them inside the tree's guides
+    #[arg(long, conflicts_with = "truncate")]
+    wrap: bool,
+
+    /// When to color entries
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t)]
+    color: style::ColorWhen,
+
+    /// Make entries clickable file:// links (OSC 8) in terminals that support it
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t)]
+    hyperlink: style::Hyperlink,
+
+    /// Clipboard backend
+    #[arg(long, value_enum, value_name = "BACKEND", default_value_t)]
+    clip: clip::Backend,
+
+    /// Wrap the text tree (or mermaid) in a markdown code fence, optionally with a LANGUAGE tag, when copying or writing it
+    #[arg(long, value_name = "LANGUAGE", num_args = 0..=1, default_missing_value = "")]
+    fence: Option<String>,
+
+    /// Don't copy to clipboard
+    #[arg(long)]
+    noclip: bool,
+
+    /// Copy an HTML rendering along with the text, for pasting into rich text editors
+    #[arg(long, conflicts_with = "noclip")]
+    clip_html: bool,
+
+    /// Keep directory listings in ~/.cache/project-tree and reuse the ones whose directory didn't change since
+    #[arg(long)]
+    cache: bool,
+
+    /// Descend into symlinked directories
+    #[arg(short = 'L', long)]
+    follow_symlinks: bool,
+
+    /// Mark files that are hardlinks of an earlier entry
+    #[arg(long)]
+    hardlinks: bool,
+
+    /// Give up on directories that take longer than SECONDS to list (hung network mounts), marking them [timed out]
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<f64>,
+
+    /// Don't descend into directories on other filesystems
+    #[arg(short = 'x', long)]
+    one_file_system: bool,
+
+    /// Only show files whose contents contain PATTERN
+    #[arg(long, value_name = "PATTERN")]
+    contains: Option<String>,
+
+    /// Show the size of files, and of everything below directories
+    #[arg(long)]
+    size: bool,
+
+    /// Measure sizes as file lengths instead of the disk space allocated to them
+    #[arg(long)]
+    apparent_size: bool,
+
+    /// Line the sizes and counts up in columns on the right instead of after each name
+    #[arg(long)]
+    align: bool,
+
+    /// After the tree, print file counts and sizes per extension (the N most common) and entry counts per depth
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+    stats: Option<usize>,
+
+    /// After the tree, print how many files fall in each size range
+    #[arg(long)]
+    size_histogram: bool,
+
+    /// Print the path of every entry shown, one per line, instead of the tree
+    #[arg(long)]
+    paths_only: bool,
+
+    /// Build the tree from the paths on stdin, one per line, instead of scanning the folder
+    #[arg(long)]
+    from_stdin: bool,
+
+    /// Like --paths-only, but ending each path with a NUL for `xargs -0` and `fzf --read0`
+    #[arg(long)]
+    print0: bool,
+
+    /// Print commands recreating the directories and (empty) files instead of the tree
+    #[arg(long, value_enum, value_name = "SHELL")]
+    emit_script: Option<script::Shell>,
+
+    /// After the tree, include the contents of every file shown, each under its path in a code fence
+    #[arg(long)]
+    contents: bool,
+
+    /// Pick the files to include in an interactive tree, then print them like --contents
+    #[arg(long)]
+    pick: bool,
+
+    /// Show sizes as proportional bars instead of the tree, biggest first
+    #[arg(long)]
+    treemap: bool,
+
+    /// Count TODO/FIXME/HACK markers per file and directory
+    #[arg(long)]
+    todos: bool,
+
+    /// Count code/comment/blank lines per file and directory, with a summary per language
+    #[arg(long)]
+    loc: bool,
+
+    /// Run COMMAND on every file, with {} replaced by its path, and show what it prints next to the name
+    #[arg(long, value_name = "COMMAND")]
+    exec: Option<String>,
+
+    /// Show the detected type of each file (png image, ELF binary, UTF-8 text, ...)
+    #[arg(long)]
+    kind: bool,
+
+    /// Show package name and description next to directories with a Cargo.toml, package.json or pyproject.toml
+    #[arg(long)]
+    packages: bool,
+
+    /// Like --packages, and collapse the contents of every package directory to a count of what's in it
+    #[arg(long)]
+    packages_only: bool,
+
+    /// Wrap the tree in a template file, filling in {{tree}}, {{date}}, {{root}}, {{count_files}} and {{count_dirs}}
+    #[arg(long, value_name = "FILE")]
+    template: Option<PathBuf>,
+
+    /// Pick entries to ignore or stop at in an interactive tree, and add them to .project-tree.toml
+    #[arg(long)]
+    edit_ignores: bool,
+
+    /// Print how long the scan took, how many directories were listed and what each kind of rule left out on stderr
+    #[arg(long)]
+    timing: bool,
+
+    /// Exit with 1 without printing or copying anything when no entries are left after ignoring and filtering
+    #[arg(long)]
+    fail_if_empty: bool,
+
+    /// Compare the tree with FILE (optionally :FORMAT), print a diff and exit with 1 if they differ
+    #[arg(long, value_name = "FILE[:FORMAT]", value_parser = output::parse_target)]
+    check: Option<output::Target>,
+
+    /// Replace the text between <!-- project-tree:start --> and <!-- project-tree:end --> in FILE with the tree
+    #[arg(long, value_name = "FILE")]
+    inject: Option<PathBuf>
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Create the directories and empty files of a rendered tree (text, markdown or JSON, - for stdin)
+    Scaffold {
+        /// Tree to create
+        file: PathBuf,
+
+        /// Directory to create it in
+        #[arg(short = 'C', long, value_name = "DIR", default_value = ".")]
+        into: PathBuf,
+    },
+    /// Show a hosted repository's tree without cloning it, e.g. github.com/owner/repo@v1.0
+    Remote {
+        /// host/owner/repo, optionally @branch, tag or commit
+        repo: String,
+    },
+    /// Serve the tree over HTTP (/ as HTML, /tree.json, /tree.txt), rescanning on every request
+    Serve {
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+
+        /// Address to listen on, 0.0.0.0 to be reachable from other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+    /// Check the tree against a spec (TOML with required/forbidden globs, or a rendered tree), exit 1 on violations
+    Verify {
+        /// Spec to check against
+        spec: PathBuf,
+    },
 }
 
 struct ProjectTree {
     ignore_list: HashSet<PathBuf>,
+    /// Names to leave out at any depth
+    ignore_names: HashSet<String>,
+    /// Globs on names, from -I
+    ignore_patterns: Vec<String>,
+    /// Globs on names files have to match, from -P
+    file_patterns: Vec<String>,
+    dirs_only: bool,
     stop_list: HashSet<PathBuf>,
-    prioritize_dirs: bool
+    /// Directory names to not recurse into at any depth
+    stop_names: HashSet<String>,
+    /// Patterns from an ignore file like .dockerignore
+    ignore_rules: Option<IgnoreRules>,
+    prioritize_dirs: bool,
+    depth: depth::Limits,
+    /// Per directory settings from the config
+    rules: Vec<config::DirRule>,
+    /// Globs of entries to show dimmed, matched against the name, or the path when they have a `/`
+    dim_patterns: Vec<String>,
+    /// Globs like the dim ones with the style for the entries they match, the least specific first
+    colors: Vec<(String, Style)>,
+    /// The user's `ls` palette or the built-in one, under the config's colors
+    ls_colors: Option<ls_colors::LsColors>,
+    /// Replaces `ls_colors`
+    theme: Option<config::Theme>,
+    /// The root .gitignore, when the theme styles ignored entries
+    gitignore: Option<IgnoreRules>,
+    collation: Collation,
+    follow_symlinks: bool,
+    visited: HashSet<FileId>,
+    /// Directories on the path down to the one being scanned, root first
+    ancestors: Vec<FileId>,
+    show_hardlinks: bool,
+    /// First path seen for each hardlinked inode
+    hardlinks: HashMap<FileId, PathBuf>,
+    timeout: Option<Duration>,
+    cache: Option<cache::Cache>,
+    /// Device of the root, set with --one-file-system
+    root_device: Option<u64>,
+    /// Only keep files containing this (and the directories leading to them)
+    contains: Option<String>,
+    count_todos: bool,
+    measure_size: bool,
+    /// Lengths instead of the space allocated on disk
+    apparent_size: bool,
+    /// Per language line counts, set with --loc
+    loc_summary: Option<LocSummary>,
+    show_kind: bool,
+    show_packages: bool,
+    packages_only: bool,
+    /// What the scan dropped where, for --timing
+    counters: timing::Counters
 }
 
 impl ProjectTree {
-    fn new(ignore_list: HashSet<PathBuf>, stop_list: HashSet<PathBuf>, prioritize_dirs: bool) -> ProjectTree {
+    /// Forgets what the previous scan saw, to scan again
+    fn reset(&mut self) {
+        self.visited.clear();
+        self.hardlinks.clear();
+        self.counters = timing::Counters::default();
+        if let Some(summary) = &mut self.loc_summary { *summary = LocSummary::default(); }
+    }
+
+    /// What --exec and --sort do to a fresh scan, for the CLI and every `serve` request alike
+    fn prepare(&mut self, entries: &mut [Entry], args: &Args) {
+        if let Some(command) = &args.exec {
+            exec::annotate(entries, command);
+        }
+        sort::apply(entries, args.sort);
+    }
+
+    fn new(ignore_list: HashSet<PathBuf>, ignore_names: HashSet<String>, stop_list: HashSet<PathBuf>, stop_names: HashSet<String>, ignore_rules: Option<IgnoreRules>, config: &Config, args: &Args) -> ProjectTree {
+        let rules: Vec<config::DirRule> = config.rules.clone();
+        //Rule depths work like --depth-for, given before the flags so those win
+        let overrides: Vec<depth::Override> = rules.iter()
+            .filter_map(|rule| Some(depth::Override { pattern: rule.pattern.clone(), levels: rule.depth? }))
+            .chain(args.depth_for.iter().cloned())
+            .collect();
         ProjectTree {
             ignore_list,
+            ignore_names,
+            ignore_patterns: args.ignore_pattern.clone(),
+            file_patterns: args.pattern.clone(),
+            dirs_only: args.dirs_only,
             stop_list,
-            prioritize_dirs
+            stop_names,
+            ignore_rules,
+            prioritize_dirs: args.dirs,
+            depth: depth::Limits { depth: args.depth, overrides },
+            rules,
+            colors: config.colors.clone(),
+            ls_colors: (!args.no_type_colors).then(ls_colors::LsColors::from_env),
+            theme: config.theme.clone(),
+            gitignore: config.theme.as_ref().and_then(|theme| theme.gitignored).map(|_| IgnoreRules::gitignore(Path::new("."))),
+            dim_patterns: config.dim.iter().chain(&args.dim).map(|pattern| pattern.trim_start_matches("./").to_owned()).collect(),
+            collation: args.collate,
+            follow_symlinks: args.follow_symlinks,
+            visited: HashSet::new(),
+            ancestors: platform::file_id(Path::new("./")).into_iter().collect(),
+            show_hardlinks: args.hardlinks,
+            hardlinks: HashMap::new(),
+            timeout: args.timeout.map(Duration::from_secs_f64),
+            cache: args.cache.then(|| cache::Cache::load(Path::new("."))).flatten(),
+            root_device: if args.one_file_system { platform::device_id(Path::new("./")) } else { None },
+            contains: args.contains.clone(),
+            count_todos: args.todos,
+            measure_size: args.size || args.stats.is_some() || args.treemap || args.size_histogram
+                || args.format == Format::HtmlReport || args.output.iter().any(|target| target.format == Some(Format::HtmlReport)),
+            apparent_size: args.apparent_size,
+            loc_summary: args.loc.then(LocSummary::default),
+            show_kind: args.kind,
+            show_packages: args.packages || args.packages_only,
+            packages_only: args.packages_only,
+            counters: timing::Counters::default()
+        }
+    }
+
+    /// Whether `name` is ignored wherever it is, by --ignore names from the config or -I
+    fn ignores_name(&self, name: &str) -> bool {
+        self.ignore_names.contains(name) || self.ignore_patterns.iter().any(|pattern| glob::matches(pattern, name))
+    }
+
+    /// Whether a file named `name` is left out by -d or -P
+    fn hides_file(&self, name: &str) -> bool {
+        self.dirs_only || (!self.file_patterns.is_empty() && !self.file_patterns.iter().any(|pattern| glob::matches(pattern, name)))
+    }
+
+    /// Rules covering the directory at `parent`, the most specific last
+    fn rules_for<'a>(&'a self, parent: &'a str) -> impl Iterator<Item = &'a config::DirRule> + 'a {
+        self.rules.iter().filter(move |rule| rule.covers(parent))
+    }
+
+    fn dirs_first(&self, parent: &str) -> bool {
+        self.rules_for(parent).filter_map(|rule| rule.dirs_first).last().unwrap_or(self.prioritize_dirs)
+    }
+
+    /// Color and dimming from the theme or LS_COLORS, the config and --dim for the entry at `path`. `class` is only
+    /// looked at when coloring by type
+    fn style_for(&self, path: &str, class: impl FnOnce() -> Class) -> Option<Style> {
+        let name: &str = path.rsplit('/').next().unwrap_or(path);
+        let matches = |pattern: &str| glob::matches(pattern, if pattern.contains('/') { path } else { name });
+        let by_type = || -> Option<Style> {
+            match (&self.theme, &self.ls_colors) {
+                (Some(theme), _) => {
+                    let class: Class = class();
+                    let gitignored: bool = self.gitignore.as_ref().is_some_and(|rules| rules.is_ignored(path, class == Class::Dir));
+                    theme.style(class, gitignored)
+                }
+                (None, Some(ls_colors)) => ls_colors.style(Path::new(path), class()),
+                (None, None) => None,
+            }
+        };
+        let colored: Option<Style> = self.colors.iter().rev().find(|(pattern, _)| matches(pattern)).map(|&(_, style)| style)
+            .or_else(by_type);
+        let dimmed: bool = self.dim_patterns.iter().any(|pattern| matches(pattern))
+            || self.rules_for(path).filter_map(|rule| rule.dim).last().unwrap_or(false);
+        match (colored, dimmed) {
+            (colored, true) => Some(colored.unwrap_or_default().merge(Style::DIM)),
+            (colored, false) => colored,
         }
     }
 
-    fn scan_folder(&self, cur_path: &PathBuf, cur_prefix: String, show_lines: bool) -> io::Result<Vec<String>> {
-        let mut files: Vec<String> = Vec::new();
+    /// Entries for a listing that didn't come from walking the filesystem, with the same ignoring, stopping and ordering
+    fn scan_listing(&self, nodes: std::collections::BTreeMap<String, listing::Node>, parent: &str, levels: Option<usize>) -> Vec<Entry> {
+        let mut entries: Vec<Entry> = Vec::new();
+        for (name, node) in nodes {
+            let path: String = if parent.is_empty() { name.clone() } else { format!("{parent}/{name}") };
+            let local: PathBuf = Path::new(".").join(&path);
+            if self.ignore_list.contains(&local) || self.ignores_name(&name) || self.rules_for(parent).any(|rule| rule.ignores(&name)) { continue; }
+            if !node.is_dir && self.hides_file(&name) { continue; }
+            let is_stopped: bool = self.stop_list.contains(&local) || self.stop_names.contains(&name) || self.rules_for(parent).any(|rule| rule.stops(&name));
 
-        let mut paths: Vec<PathBuf> = fs::read_dir(&cur_path)?.filter_map(|entry| {
-            let entry: fs::DirEntry = entry.ok()?;
-            let path: PathBuf = entry.path();
-            if self.ignore_list.contains(&path) { None } else { Some(path) }
-        }).collect();
+            let size: u64 = node.total_size();
+            let below: Option<usize> = self.depth.below(&path, &name, levels);
+            let children: Vec<Entry> = if is_stopped || below == Some(0) { Vec::new() } else { self.scan_listing(node.children, &path, below) };
+            entries.push(Entry {
+                name: self.collation.display(&name),
+                path: self.collation.display(&path),
+                is_dir: node.is_dir,
+                notes: Vec::new(),
+                style: self.style_for(&path, || if node.is_dir { Class::Dir } else { Class::File }),
+                todos: TodoCounts::default(),
+                loc: LocCounts::default(),
+                size,
+                output: String::new(),
+                children,
+            });
+        }
 
-        if self.prioritize_dirs {
-            paths.sort_by_key(|path| !path.is_dir());
+        entries.sort_by_cached_key(|entry| self.collation.key(&entry.name));
+        if self.dirs_first(parent) {
+            entries.sort_by_key(|entry| !entry.is_dir);
         }
+        entries
+    }
 
-        for (i, path) in paths.iter().enumerate() {
-            let is_dir: bool = path.is_dir();
-            let is_last: bool = i == paths.len() - 1;
+    /// Lists `cur_path`, and what's below it down to `levels` levels (`None` for all of them)
+    fn scan_folder(&mut self, cur_path: &Path, levels: Option<usize>) -> io::Result<Vec<Entry>> {
+        let mut entries: Vec<Entry> = Vec::new();
+
+        //Symlinks can lead back up the tree, remember where we've been so we don't loop forever
+        if self.follow_symlinks {
+            if let Some(id) = platform::file_id(cur_path) { self.visited.insert(id); }
+        }
 
-            let affix = match (show_lines, is_last) {
-                (true, true) => "└── ",
-                (true, false) => "├── ",
-                (false, _) => "",
+        let parent: String = relative(cur_path);
+        let timeout: Option<Duration> = self.timeout;
+        let mut children: Vec<Child> = match &mut self.cache {
+            Some(cache) => cache.list(cur_path, || list_dir(cur_path, timeout))?,
+            None => list_dir(cur_path, timeout)?.into_iter().map(Child::stat).collect(),
+        };
+        self.counters.dirs += 1;
+        //Paths, names, rules
+        let mut ignored: [usize; 3] = [0; 3];
+        children.retain(|child| {
+            let name: std::borrow::Cow<str> = child.path.file_name().unwrap_or_default().to_string_lossy();
+            let category: Option<usize> = if self.ignore_list.contains(&child.path) {
+                Some(0)
+            } else if self.ignores_name(&name) {
+                Some(1)
+            } else if self.rules_for(&parent).any(|rule| rule.ignores(&name)) {
+                Some(2)
+            } else {
+                None
             };
+            if let Some(category) = category { ignored[category] += 1; }
+            category.is_none()
+        });
+        self.counters.ignored_paths += ignored[0];
+        self.counters.ignored_names += ignored[1];
+        self.counters.ignored_by_rules += ignored[2];
+
+        children.sort_by_cached_key(|child| self.collation.key(&child.path.file_name().unwrap_or_default().to_string_lossy()));
+        if self.dirs_first(&parent) {
+            children.sort_by_key(|child| !child.is_dir);
+        }
+
+        for Child { path, is_dir, is_link, is_special } in children {
+            //Stop where we are, the caller prints the partial tree
+            if interrupt::requested() { break; }
+
+            //Excluded directories are still scanned when a `!pattern` could bring back something inside them
+            let is_excluded: bool = self.ignore_rules.as_ref().is_some_and(|rules| rules.is_ignored(&relative(&path), is_dir));
+            if is_excluded && !(is_dir && self.ignore_rules.as_ref().is_some_and(IgnoreRules::has_exceptions)) {
+                self.counters.excluded += 1;
+                continue;
+            }
+
+            if !is_dir && self.hides_file(&path.file_name().unwrap_or_default().to_string_lossy()) {
+                continue;
+            }
+
+            if let Some(pattern) = &self.contains {
+                //is_file() so FIFOs and devices are never opened, directories are checked once scanned
+                let keep: bool = is_dir || (path.is_file() && content::file_contains(&path, pattern));
+                if !keep {
+                    self.counters.not_containing += 1;
+                    continue;
+                }
+            }
+
             let filename: &std::ffi::OsStr = path.file_name().unwrap_or_default();
-            let filename: &str = filename.to_str().unwrap_or_default();
+            let is_utf8: bool = filename.to_str().is_some();
+            let name: String = self.collation.display(&filename.to_string_lossy());
+
+            let mut notes: Vec<String> = Vec::new();
+            let mut style: Option<Style> = None;
+            if !is_utf8 {
+                notes.push(String::from("[invalid utf-8]"));
+            }
+            if is_link {
+                match fs::read_link(&path) {
+                    Ok(target) => notes.push(format!("-> {}", target.display())),
+                    //Reparse points that aren't symlinks or junctions (dedup, cloud placeholders) have no target
+                    Err(_) => notes.push(String::from("[link]")),
+                }
+                //exists() follows the link, so a dangling one reports false
+                if !path.exists() {
+                    notes.push(String::from("[broken]"));
+                    //LS_COLORS and themes have their own color for these
+                    if self.ls_colors.is_none() && self.theme.is_none() { style = Some(Style::RED); }
+                }
+            }
 
-            files.push(format!("{cur_prefix}{affix}{filename}{}", if is_dir { "/" } else { "" }));     
+            let package: Option<manifest::Package> = (self.show_packages && is_dir).then(|| manifest::detect(&path)).flatten();
+            if let Some(package) = &package {
+                notes.push(package.to_string());
+            }
 
-            if is_dir && !self.stop_list.contains(path) {
-                let new_prefix = format!("{cur_prefix}{}", if is_last { "    " } else { "│   " });
+            if let Some(kind) = is_special.then(|| platform::special_kind(&path)).flatten() {
+                notes.push(format!("[{kind}]"));
+            } else if self.show_kind && path.is_file() {
+                if let Some(kind) = content::sniff_kind(&path) {
+                    notes.push(format!("[{kind}]"));
+                }
+            }
+
+            if let Some(id) = self.show_hardlinks.then(|| platform::hardlink_id(&path)).flatten() {
+                match self.hardlinks.get(&id) {
+                    Some(first) => notes.push(format!("=> same as {}", relative(first))),
+                    None => { self.hardlinks.insert(id, path.clone()); }
+                }
+            }
+
+            let is_stopped: bool = self.stop_list.contains(&path) || self.stop_names.contains(&name) || self.rules_for(&parent).any(|rule| rule.stops(&name));
+            let below: Option<usize> = self.depth.below(&relative(&path), &name, levels);
+            let mut recurse: bool = is_dir && !is_stopped && below != Some(0) && (!is_link || self.follow_symlinks);
+            if is_dir && is_stopped {
+                self.counters.stopped += 1;
+            } else if is_dir && below == Some(0) {
+                self.counters.depth_limited += 1;
+            }
+            let id: Option<FileId> = recurse.then(|| platform::file_id(&path)).flatten();
+            //Bind mounts and junctions can loop even without following symlinks
+            if recurse && id.as_ref().is_some_and(|id| self.ancestors.contains(id)) {
+                notes.push(String::from("[loop detected]"));
+                recurse = false;
+            }
+            //Only links are held back, the real directory is always shown even if a link to it came first
+            if recurse && is_link && id.as_ref().is_some_and(|id| self.visited.contains(id)) {
+                notes.push(String::from("[recursive, not followed]"));
+                recurse = false;
+            }
+            if recurse && self.root_device.is_some() && platform::device_id(&path) != self.root_device {
+                notes.push(String::from("[other filesystem]"));
+                recurse = false;
+            }
 
-                let mut sub_files: Vec<String> = self.scan_folder(path,new_prefix,true)?;
-                files.append(&mut sub_files);
+            let mut children: Vec<Entry> = Vec::new();
+            if recurse {
+                self.ancestors.extend(id.iter().cloned());
+                //An unreadable subdirectory shouldn't cost us the rest of the tree
+                match self.scan_folder(&path, below) {
+                    Ok(scanned) => children = scanned,
+                    Err(err) if err.kind() == io::ErrorKind::TimedOut => notes.push(String::from("[timed out]")),
+                    Err(err) => notes.push(format!("[cannot read: {}]", err.kind())),
+                }
+                if id.is_some() {
+                    self.ancestors.pop();
+                }
             }
+
+            if (self.contains.is_some() || is_excluded) && is_dir && children.is_empty() {
+                if is_excluded { self.counters.excluded += 1; } else { self.counters.not_containing += 1; }
+                continue;
+            }
+
+            let mut todos: TodoCounts = TodoCounts::default();
+            if self.count_todos {
+                if path.is_file() {
+                    todos = TodoCounts::of_file(&path);
+                }
+                for child in &children {
+                    todos += child.todos;
+                }
+            }
+
+            let mut loc: LocCounts = LocCounts::default();
+            if let Some(summary) = &mut self.loc_summary {
+                if let Some((language, counts)) = path.is_file().then(|| loc::count_file(&path)).flatten() {
+                    summary.add(language, counts);
+                    loc = counts;
+                }
+                for child in &children {
+                    loc += child.loc;
+                }
+            }
+
+            let mut size: u64 = 0;
+            if self.measure_size {
+                size = if recurse {
+                    children.iter().map(|child| child.size).sum()
+                } else if is_dir && !is_link {
+                    //Stopped directories still count, they're usually what's eating the space
+                    size::of_dir(&path, self.apparent_size)
+                } else {
+                    size::of_file(&path, self.apparent_size)
+                };
+            }
+
+            if style.is_none() {
+                style = self.style_for(&relative(&path), || Class::of(&path));
+            }
+
+            //Collapsed after the sizes and counts were rolled up, so those still cover the whole package
+            if self.packages_only && package.is_some() && !children.is_empty() {
+                let (files, dirs) = entry::count_kinds(&children);
+                notes.push(format!("[{files} files, {dirs} dirs]"));
+                children.clear();
+            }
+
+            entries.push(Entry {
+                name,
+                path: self.collation.display(&relative(&path)),
+                is_dir,
+                notes,
+                style,
+                todos,
+                loc,
+                size,
+                output: String::new(),
+                children,
+            });
         }
 
-        
+        Ok(entries)
+    }
+}
+
+/// Paths in a directory. With a timeout the listing happens on another thread, which is left behind if it hangs
+/// (a stuck NFS/SMB call can't be cancelled), so one dead mount doesn't freeze the whole scan
+fn list_dir(path: &Path, timeout: Option<Duration>) -> io::Result<Vec<PathBuf>> {
+    let list = |path: &Path| -> io::Result<Vec<PathBuf>> {
+        Ok(fs::read_dir(path)?.filter_map(|entry| Some(entry.ok()?.path())).collect())
+    };
+    let Some(timeout) = timeout else { return list(path) };
+
+    let (sender, receiver) = mpsc::channel();
+    let owned_path: PathBuf = path.to_path_buf();
+    thread::spawn(move || sender.send(list(&owned_path)));
+    receiver.recv_timeout(timeout).unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()))
+}
+
+/// `./src/main.rs` -> `src/main.rs`, always `/` separated like ignore patterns
+fn relative(path: &Path) -> String {
+    let segments: Vec<std::borrow::Cow<str>> = path.strip_prefix(".").unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    segments.join("/")
+}
+
+/// Config entries without a `/` are names matched at any depth, the rest are paths from the root
+fn add_config_entry(entry: &str, names: &mut HashSet<String>, paths: &mut HashSet<PathBuf>) {
+    let entry: &str = entry.trim_end_matches('/');
+    if entry.contains('/') {
+        paths.insert(Path::new(".").join(entry.trim_start_matches("./")));
+    } else {
+        names.insert(entry.to_owned());
+    }
+}
+
+/// `file`, or stdin for `-`
+fn read_input(file: &Path) -> io::Result<String> {
+    if file == Path::new("-") {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(file)
+    }
+}
+
+fn scaffold(file: &Path, into: &Path) -> io::Result<()> {
+    let nodes: Vec<scaffold::Node> = scaffold::parse(&read_input(file)?)
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {message}", file.display())))?;
+    let created: scaffold::Created = scaffold::create(&nodes, into)?;
+    println!("created {} directories and {} files", created.dirs, created.files);
+    if created.skipped > 0 {
+        println!("left {} existing files alone", created.skipped);
+    }
+    Ok(())
+}
 
-        Ok(files)
+/// The tree in `format` with everything the flags put around or instead of it (paths, scripts, summaries, contents)
+fn render_tree(entries: &[Entry], format: Format, options: &RenderOptions, args: &Args, root_dir: Option<&str>, loc_summary: Option<&LocSummary>, interrupted: bool) -> String {
+    //Exact paths for other programs, whatever the format
+    if args.print0 {
+        return entry::paths(entries).iter().map(|path| format!("{path}\0")).collect();
+    }
+    if args.paths_only {
+        let paths: Vec<String> = entry::paths(entries).iter()
+            .map(|path| if options.literal { path.clone() } else { style::escape_control(path).into_owned() })
+            .collect();
+        return paths.join("\n");
+    }
+    //A script is the whole output, no summaries after it
+    if let Some(shell) = args.emit_script {
+        match format {
+            Format::Text => return shell.script(entries),
+            Format::Md => return format!("```{}\n{}\n```", shell.fence_language(), shell.script(entries)),
+            _ => {}
+        }
+    }
+    let mut rendered: String = match format {
+        Format::Text if args.treemap => treemap::render(entries),
+        Format::Md if args.treemap => format!("```\n{}\n```", treemap::render(entries)),
+        _ => format.render(entries, root_dir, options),
+    };
+    if interrupted {
+        match format {
+            Format::Text | Format::Md | Format::PromptXml => rendered.push_str("\n[interrupted, tree is incomplete]"),
+            _ => {}
+        }
+    }
+    let tables = loc_summary.map(LocSummary::table)
+        .into_iter()
+        .chain(args.stats.map(|top| stats::extension_table(entries, top)))
+        .chain(args.stats.map(|_| stats::depth_table(entries)))
+        .chain(args.size_histogram.then(|| stats::size_histogram(entries)));
+    for table in tables {
+        match format {
+            Format::Text => rendered = format!("{rendered}\n\n{table}"),
+            Format::Md => rendered = format!("{rendered}\n\n```\n{table}\n```"),
+            _ => {}
+        }
     }
+    //prompt-xml has the contents already
+    if (args.contents || args.pick) && matches!(format, Format::Text | Format::Md) {
+        rendered = format!("{rendered}\n\n{}", dump::render(entries));
+    }
+    rendered
 }
 
 fn main() -> io::Result<()>{
-    let args = Args::parse();
-    let mut clipboard: ClipboardContext = ClipboardProvider::new().unwrap();
+    let mut args = Args::parse_from(compat::gnu_args(std::env::args_os()));
+    if let Some(compat) = args.compat {
+        args.format = compat.into();
+    }
+
+    if let Some(Command::Scaffold { file, into }) = &args.command {
+        return scaffold(file, into);
+    }
+
+    let remote: Option<remote::RepoSpec> = match &args.command {
+        Some(Command::Remote { repo }) => Some(remote::RepoSpec::parse(repo).map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?),
+        _ => None,
+    };
+    if remote.is_some() && args.from_stdin {
+        eprintln!("error: --from-stdin can't be combined with a remote repository");
+        std::process::exit(2);
+    }
+    //Only names and sizes come from the hosting API, these would read or run the same paths on this machine instead
+    if remote.is_some() {
+        let local_only: [(bool, &str); 6] = [
+            (args.contents, "--contents"),
+            (args.pick, "--pick"),
+            (args.exec.is_some(), "--exec"),
+            (args.format == Format::PromptXml, "--format prompt-xml"),
+            (args.format == Format::Ncdu, "--format ncdu"),
+            (matches!(args.format, Format::TreeJson | Format::TreeXml), "--compat"),
+        ];
+        if let Some((_, flag)) = local_only.iter().find(|(set, _)| *set) {
+            eprintln!("error: {flag} reads files on this machine and can't be combined with a remote repository");
+            std::process::exit(2);
+        }
+    }
+
+    let mut config: Config = Config::load()?;
+    if let Some(theme) = &args.theme {
+        config.theme = Some(config::Theme::load(theme)?);
+    }
 
     let mut ignore_list: HashSet<PathBuf> = HashSet::new();
-    if !args.git { ignore_list.insert(PathBuf::from("./.git")); }
-    if !args.vscode { ignore_list.insert(PathBuf::from("./.vscode")); }
-    for ignore in args.ignore {
+    let mut ignore_names: HashSet<String> = HashSet::new();
+    //The built-in ones are only the root's, like they always were
+    let mut default_ignore: Vec<String> = config.default_ignore.clone().unwrap_or_else(|| vec![String::from("./.git"), String::from("./.vscode")]);
+    if args.all { default_ignore.clear(); }
+    if args.git { default_ignore.retain(|name| name.trim_start_matches("./") != ".git"); }
+    if args.vscode { default_ignore.retain(|name| name.trim_start_matches("./") != ".vscode"); }
+    for ignore in default_ignore.iter().chain(&config.ignore) {
+        add_config_entry(ignore, &mut ignore_names, &mut ignore_list);
+    }
+    for ignore in &args.ignore {
         //See next comment
         if ignore.starts_with("./") {
             ignore_list.insert(PathBuf::from(ignore));
@@ -124,8 +984,20 @@ fn main() -> io::Result<()>{
     }
 
     let mut stop_list: HashSet<PathBuf> = HashSet::new();
-    if !args.node_modules { stop_list.insert(PathBuf::from("./node_modules")); }
-    for stop in args.stop {
+    let mut stop_names: HashSet<String> = HashSet::new();
+    let mut default_stop: Vec<String> = config.default_stop.clone().unwrap_or_else(|| {
+        let mut default_stop: Vec<String> = vec![String::from("./node_modules")];
+        //Detection looks at the local manifests, which say nothing about a remote repository
+        if !args.no_auto_stop && remote.is_none() {
+            default_stop.extend(ecosystem::detect_stops(Path::new(".")).into_iter().map(String::from));
+        }
+        default_stop
+    });
+    if args.node_modules { default_stop.retain(|name| name.trim_start_matches("./") != "node_modules"); }
+    for stop in default_stop.iter().chain(&config.stop) {
+        add_config_entry(stop, &mut stop_names, &mut stop_list);
+    }
+    for stop in &args.stop {
         //Yes yes its ugly but it works, I can't figure out how to hash a PathBuf and support relative paths
         if stop.starts_with("./") {
             stop_list.insert(PathBuf::from(stop));
@@ -134,22 +1006,215 @@ fn main() -> io::Result<()>{
         }
     }
 
-    let mut tree: String = ProjectTree::new(ignore_list, stop_list, args.dirs)
-                                   .scan_folder(&PathBuf::from("./"), String::from(""), args.root)
-                                   .unwrap()
-                                   .join("\n");
+    let ignore_rules: Option<IgnoreRules> = if args.dockerignore {
+        Some(IgnoreRules::dockerignore(Path::new(".dockerignore"))?)
+    } else if args.npm_pack {
+        Some(IgnoreRules::npm_pack(Path::new("."))?)
+    } else {
+        None
+    };
+
+    //A remote listing has nothing on this machine to resolve against
+    let absolute_root: Option<String> = match (&remote, args.absolute) {
+        (Some(_), true) => {
+            eprintln!("error: --absolute needs a local folder, not a remote repository");
+            std::process::exit(2);
+        }
+        (None, true) => Some(fs::canonicalize(".")?.to_string_lossy().into_owned()),
+        _ => None,
+    };
+
+    interrupt::install();
+    let started: Instant = Instant::now();
+    let mut project_tree: ProjectTree = ProjectTree::new(ignore_list, ignore_names, stop_list, stop_names, ignore_rules, &config, &args);
+    let mut entries: Vec<Entry> = match &remote {
+        Some(spec) => project_tree.scan_listing(listing::nest(remote::list(spec)?), "", project_tree.depth.root()),
+        None if args.from_stdin => project_tree.scan_listing(listing::nest(listing::parse(&io::read_to_string(io::stdin())?)), "", project_tree.depth.root()),
+        None => project_tree.scan_folder(Path::new("./"), project_tree.depth.root())?,
+    };
+    if let Some(cache) = &mut project_tree.cache {
+        cache.save();
+    }
+    let scanned: Duration = started.elapsed();
+    if let Some(Command::Verify { spec }) = &args.command {
+        let spec_text: String = read_input(spec)?;
+        let violations: Vec<String> = verify::Spec::load(spec, &spec_text)?.check(&entries);
+        for violation in &violations {
+            println!("{violation}");
+        }
+        if !violations.is_empty() {
+            std::process::exit(1);
+        }
+        println!("ok");
+        return Ok(());
+    }
+
+    if args.edit_ignores {
+        match builder::run(&entries)? {
+            Some(saved) => println!("added {saved} entries to {}", config::FILE_NAME),
+            None => println!("cancelled, {} left unchanged", config::FILE_NAME),
+        }
+        return Ok(());
+    }
+
+    let interrupted: bool = interrupt::requested();
+    if interrupted {
+        eprintln!("interrupted, showing what was scanned so far (Ctrl-C again to quit)");
+    } else {
+        project_tree.prepare(&mut entries, &args);
+        if args.pick && !picker::run(&mut entries)? {
+            return Ok(());
+        }
+    }
+    if let Some(root) = &absolute_root {
+        entry::absolutize(&mut entries, root);
+    }
 
     //Get Root Dir Name
-    if args.root {
-        let root_dir: String = std::env::current_dir().unwrap().file_name().unwrap().to_str().unwrap().to_owned();
-        tree = format!("{root_dir}\n{tree}");
+    let root_dir: Option<String> = args.root.then(|| match &remote {
+        Some(spec) => spec.name.clone(),
+        None => std::env::current_dir().unwrap().file_name().unwrap().to_str().unwrap().to_owned(),
+    });
+
+    //Piped into another program: no escape codes, and don't clobber the clipboard
+    let is_tty: bool = io::stdout().is_terminal();
+
+    let repo_links: Option<repo::RepoLinks> = if args.repo_links {
+        repo::RepoLinks::detect().map_err(|err| eprintln!("warning: no repository links: {err}")).ok()
+    } else {
+        None
+    };
+    //Files, the clipboard and everything else that isn't the screen get plain text, escape codes would end up in pastes
+    let options = RenderOptions {
+        color: false,
+        //Without the indentation the path is what tells where an entry is
+        full_path: args.full_path || args.accessible || args.absolute,
+        quote: args.quote,
+        literal: args.literal,
+        glyphs: if args.no_lines { format::Glyphs::indent() } else { args.glyphs.clone().unwrap_or_else(|| args.charset.glyphs()) },
+        accessible: args.accessible,
+        dir_suffix: args.dir_suffix.clone().or_else(|| config.dir_suffix.clone()).map(|suffix| if suffix == "none" { String::new() } else { suffix }),
+        hyperlink_base: None,
+        repo_links,
+        emoji: args.emoji,
+        icons: false,
+        size: args.size,
+        todos: args.todos,
+        loc: args.loc,
+        exec: args.exec.is_some(),
+        align: args.align,
+        max_width: None,
+        wrap: args.wrap,
+    };
+
+    if let Some(Command::Serve { port, host }) = &args.command {
+        return serve::run(host, *port, |format| {
+            project_tree.reset();
+            let mut entries: Vec<Entry> = project_tree.scan_folder(Path::new("./"), project_tree.depth.root())?;
+            if let Some(cache) = &mut project_tree.cache {
+                cache.save();
+            }
+            project_tree.prepare(&mut entries, &args);
+            if let Some(root) = &absolute_root {
+                entry::absolutize(&mut entries, root);
+            }
+            Ok(render_tree(&entries, format, &options, &args, root_dir.as_deref(), project_tree.loc_summary.as_ref(), false))
+        });
     }
 
-    println!("{tree}");
-    if let Some(output_file) = args.output {
-        fs::write(output_file, &tree)?;
+    //Before anything gets printed or copied, so the clipboard keeps what was there
+    if args.fail_if_empty && entries.is_empty() {
+        eprintln!("error: the tree is empty, check the ignore and stop options");
+        std::process::exit(1);
     }
-    clipboard.set_contents(tree).unwrap();
 
+    let render = |format: Format, options: &RenderOptions| -> String {
+        render_tree(&entries, format, options, &args, root_dir.as_deref(), project_tree.loc_summary.as_ref(), interrupted)
+    };
+
+    if let Some(expected) = &args.check {
+        let expected_text: String = fs::read_to_string(&expected.path)?;
+        let actual: String = render(expected.format.unwrap_or(args.format), &options);
+        let diff: String = diff::unified(&expected_text, &actual, &expected.path.display().to_string(), "project-tree");
+        if diff.is_empty() {
+            return Ok(());
+        }
+        print!("{diff}");
+        std::process::exit(1);
+    }
+
+    let template: Option<String> = args.template.as_ref().map(fs::read_to_string).transpose()?;
+    let current_dir: PathBuf = std::env::current_dir()?;
+    let wrap = |tree: String| -> String {
+        let Some(template) = &template else { return tree };
+        let root: std::borrow::Cow<str> = match &remote {
+            Some(spec) => std::borrow::Cow::Borrowed(&spec.name),
+            None => current_dir.file_name().unwrap_or_default().to_string_lossy(),
+        };
+        template::fill(template, &template::Context { tree: &tree, root: &root, entries: &entries })
+    };
+
+    //The marked section already is the document, so it gets the bare tree
+    if let Some(inject_file) = &args.inject {
+        output::inject(inject_file, &render(Format::Text, &options))?;
+    }
+    let tree: String = wrap(render(args.format, &options));
+
+    //Colors, links, glyphs and fitting the terminal only make sense on screen (the glyphs would be boxes wherever the
+    //font isn't installed)
+    let screen_options = RenderOptions {
+        color: args.color.enabled(is_tty),
+        hyperlink_base: (is_tty && args.hyperlink.enabled()).then(|| current_dir.clone()),
+        max_width: (args.truncate || args.wrap).then(platform::terminal_width).flatten(),
+        icons: args.icons.is_some_and(|icons| icons == icons::Icons::Always || (is_tty && icons.enabled())),
+        ..options.clone()
+    };
+    let screen: String = if args.format == Format::Text && (screen_options.color || screen_options.hyperlink_base.is_some() || screen_options.max_width.is_some() || screen_options.icons) {
+        wrap(render(Format::Text, &screen_options))
+    } else {
+        tree.clone()
+    };
+
+    //The reader going away (`project-tree | head`) is not an error. NUL separated paths already end in their separator
+    let printed: io::Result<()> = if args.print0 { write!(io::stdout(), "{screen}") } else { writeln!(io::stdout(), "{screen}") };
+    if let Err(err) = printed {
+        if err.kind() != io::ErrorKind::BrokenPipe { return Err(err); }
+    }
+    //Only the text tree and mermaid, which markdown renders from a fence, get fenced. The other formats are documents
+    //of their own
+    let fence = |format: Format, text: String| -> String {
+        match &args.fence {
+            Some(language) if matches!(format, Format::Text | Format::Mermaid) => {
+                let fence: String = dump::fence_for(&text);
+                let language: &str = if language.is_empty() && format == Format::Mermaid { "mermaid" } else { language };
+                format!("{fence}{language}\n{text}\n{fence}")
+            }
+            _ => text,
+        }
+    };
+    for target in &args.output {
+        let format: Format = target.format.unwrap_or(args.format);
+        let rendered: String = if format == args.format { tree.clone() } else { render(format, &options) };
+        fs::write(&target.path, fence(format, rendered))?;
+    }
+    if !args.noclip && is_tty {
+        let copied = if args.clip_html {
+            clip::copy_rich(fence(args.format, tree), &render(Format::Html, &options), args.clip)
+        } else {
+            clip::copy(fence(args.format, tree), args.clip)
+        };
+        if let Err(err) = copied {
+            eprintln!("warning: could not copy to clipboard: {err}");
+        }
+    }
+
+    if args.timing {
+        let (files, dirs) = entry::count_kinds(&entries);
+        eprintln!("{}", project_tree.counters.report(scanned, started.elapsed(), files + dirs));
+    }
+
+    if interrupted {
+        std::process::exit(interrupt::EXIT_CODE);
+    }
     Ok(())
 }
@@ -0,0 +1,68 @@
+//! Package manifests (Cargo.toml, package.json, pyproject.toml, go.mod) found in directories.
+
+use crate::json::Json;
+use crate::toml;
+use std::fs;
+use std::path::Path;
+
+pub struct Package {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl std::fmt::Display for Package {
+    /// `— name: "description"`
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "— {}", self.name)?;
+        if let Some(description) = &self.description {
+            write!(f, ": \"{description}\"")?;
+        }
+        Ok(())
+    }
+}
+
+const MANIFESTS: [&str; 4] = ["Cargo.toml", "package.json", "pyproject.toml", "go.mod"];
+
+/// The package `dir` is the root of, if it has a manifest. Manifests without a name (virtual workspaces, unnamed
+/// projects) go by the directory's name, the directory is still a package boundary
+pub fn detect(dir: &Path) -> Option<Package> {
+    cargo(dir).or_else(|| npm(dir)).or_else(|| python(dir)).or_else(|| go(dir)).or_else(|| {
+        if !MANIFESTS.iter().any(|manifest| dir.join(manifest).is_file()) {
+            return None;
+        }
+        Some(Package { name: dir.file_name()?.to_string_lossy().into_owned(), description: None })
+    })
+}
+
+fn cargo(dir: &Path) -> Option<Package> {
+    let manifest: toml::Table = toml::parse(&fs::read_to_string(dir.join("Cargo.toml")).ok()?).ok()?;
+    from_toml(&manifest, "package")
+}
+
+fn python(dir: &Path) -> Option<Package> {
+    let manifest: toml::Table = toml::parse(&fs::read_to_string(dir.join("pyproject.toml")).ok()?).ok()?;
+    from_toml(&manifest, "project").or_else(|| from_toml(&manifest, "tool.poetry"))
+}
+
+fn npm(dir: &Path) -> Option<Package> {
+    let manifest: Json = Json::parse(&fs::read_to_string(dir.join("package.json")).ok()?)?;
+    Some(Package {
+        name: manifest.get("name")?.as_str()?.to_owned(),
+        description: manifest.get("description").and_then(Json::as_str).map(str::to_owned),
+    })
+}
+
+/// The module path, e.g. `github.com/owner/repo/tool`
+fn go(dir: &Path) -> Option<Package> {
+    let manifest: String = fs::read_to_string(dir.join("go.mod")).ok()?;
+    let module: &str = manifest.lines().find_map(|line| line.trim().strip_prefix("module "))?;
+    Some(Package { name: module.trim().trim_matches('"').to_owned(), description: None })
+}
+
+/// Name and description from `section`, e.g. `[package]`. Workspace inherited descriptions are tables and get skipped
+fn from_toml(manifest: &toml::Table, section: &str) -> Option<Package> {
+    Some(Package {
+        name: toml::get(manifest, &format!("{section}.name"))?.as_str()?.to_owned(),
+        description: toml::get(manifest, &format!("{section}.description")).and_then(toml::Value::as_str).map(str::to_owned),
+    })
+}
@@ -0,0 +1,61 @@
+//! `--format ncdu`, ncdu's JSON export, to browse the scan with `ncdu -f`.
+//!
+//! A directory is an array of its own info object followed by its children, a file (or a link, followed or not) is just
+//! its info object. Both sizes are stat'ed here, ncdu shows apparent and disk usage side by side whatever
+//! `--apparent-size` says.
+
+use crate::entry::Entry;
+use crate::format::json_string;
+use crate::platform;
+use crate::size;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `[1, 2, {metadata}, [{root}, children...]]`, `root` being the absolute path of the scanned directory
+pub fn render(entries: &[Entry], root: &Path) -> String {
+    let timestamp: u64 = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let mut items: Vec<String> = vec![info(&root.to_string_lossy(), root)];
+    items.extend(entries.iter().map(item));
+    format!(
+        "[1,2,{{\"progname\":\"project-tree\",\"progver\":\"{}\",\"timestamp\":{timestamp}}},\n[{}]]\n",
+        env!("CARGO_PKG_VERSION"),
+        items.join(",\n"),
+    )
+}
+
+fn item(entry: &Entry) -> String {
+    let path: &Path = Path::new(&entry.path);
+    if !entry.is_dir || platform::is_link(path) {
+        return info(&entry.name, path);
+    }
+    //ncdu adds up the children itself, a directory the walk stopped at has none so it carries what's below it
+    let info: String = if entry.children.is_empty() { stopped_info(&entry.name, path) } else { info(&entry.name, path) };
+    let children: Vec<String> = entry.children.iter().map(item).collect();
+    format!("[{info}{}{}]", if children.is_empty() { "" } else { ",\n" }, children.join(",\n"))
+}
+
+/// Name, sizes, and `notreg` for links, FIFOs and the like, which ncdu leaves out of totals
+fn info(name: &str, path: &Path) -> String {
+    sized_info(name, path, 0, 0)
+}
+
+/// Like `info`, with everything below the directory added to its own size
+fn stopped_info(name: &str, path: &Path) -> String {
+    sized_info(name, path, size::of_dir(path, true), size::of_dir(path, false))
+}
+
+fn sized_info(name: &str, path: &Path, asize_below: u64, dsize_below: u64) -> String {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return format!("{{\"name\":{},\"read_error\":true}}", json_string(name));
+    };
+    let mut fields: Vec<String> = vec![
+        format!("\"name\":{}", json_string(name)),
+        format!("\"asize\":{}", metadata.len() + asize_below),
+        format!("\"dsize\":{}", platform::allocated_size(&metadata).unwrap_or(metadata.len()) + dsize_below),
+    ];
+    if !metadata.is_file() && !metadata.is_dir() {
+        fields.push(String::from("\"notreg\":true"));
+    }
+    format!("{{{}}}", fields.join(","))
+}
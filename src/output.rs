@@ -0,0 +1,51 @@
+//! Where the rendered tree ends up besides stdout and the clipboard.
+
+use crate::dump;
+use crate::format::Format;
+use clap::ValueEnum;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An `--output` file and the format to write it in, `None` for the `--format` one
+#[derive(Clone, Debug)]
+pub struct Target {
+    pub path: PathBuf,
+    pub format: Option<Format>,
+}
+
+/// Parses `FILE[:FORMAT]`. The suffix only counts when it names a format, so `C:\tree.txt` stays a path
+pub fn parse_target(value: &str) -> Result<Target, String> {
+    if let Some((path, format)) = value.rsplit_once(':') {
+        if let Ok(format) = Format::from_str(format, true) {
+            if path.is_empty() {
+                return Err(String::from("missing file before the format"));
+            }
+            return Ok(Target { path: PathBuf::from(path), format: Some(format) });
+        }
+    }
+    Ok(Target { path: PathBuf::from(value), format: None })
+}
+
+const INJECT_START: &str = "<!-- project-tree:start -->";
+const INJECT_END: &str = "<!-- project-tree:end -->";
+
+/// Replaces whatever is between the project-tree markers in `file` with `tree` in a code fence, leaving the rest untouched
+pub fn inject(file: &Path, tree: &str) -> io::Result<()> {
+    let contents: String = fs::read_to_string(file)?;
+
+    let missing_markers = || io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} needs {INJECT_START} and {INJECT_END} markers to inject the tree", file.display()),
+    );
+    let start: usize = contents.find(INJECT_START).ok_or_else(missing_markers)? + INJECT_START.len();
+    let end: usize = start + contents[start..].find(INJECT_END).ok_or_else(missing_markers)?;
+
+    //Longer than any backtick run in the tree, e.g. from the --contents of a markdown file
+    let fence: String = dump::fence_for(tree);
+    let injected: String = format!("{}\n{fence}\n{tree}\n{fence}\n{}", &contents[..start], &contents[end..]);
+    if injected != contents {
+        fs::write(file, injected)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! `--pick`, choosing in the tree which files go into the `--contents` export, with a running token total.
+
+use crate::dump::{self, FileText};
+use crate::entry::Entry;
+use crate::format::{Format, RenderOptions};
+use crate::tui::{Key, Terminal};
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+struct Line {
+    text: String,
+    path: String,
+    is_dir: bool,
+    /// Lines of the files this line stands for, itself for a file and everything below for a directory
+    files: Vec<usize>,
+}
+
+const HELP: &str = "↑/↓ move  space select  a all  n none  enter export  q cancel";
+
+/// Lets the user pick files, then drops everything else from `entries`. `false` when cancelled
+pub fn run(entries: &mut Vec<Entry>) -> io::Result<bool> {
+    let rendered: String = Format::Text.render(entries, None, &RenderOptions::default());
+    let mut lines: Vec<Line> = Vec::new();
+    flatten(entries, &mut lines);
+    for (line, text) in lines.iter_mut().zip(rendered.lines()) {
+        line.text = text.to_owned();
+    }
+    if lines.is_empty() {
+        return Ok(true);
+    }
+
+    let mut selected: Vec<bool> = vec![false; lines.len()];
+    let mut tokens: Vec<Option<usize>> = vec![None; lines.len()];
+    let mut cursor: usize = 0;
+    let mut scroll: usize = 0;
+    let mut terminal: Terminal = Terminal::open()?;
+    loop {
+        let (width, height) = terminal.size();
+        let visible: usize = height.saturating_sub(2).max(1);
+        if cursor < scroll { scroll = cursor; }
+        if cursor >= scroll + visible { scroll = cursor + 1 - visible; }
+
+        let count: usize = (0..lines.len()).filter(|&i| selected[i]).count();
+        let total: usize = (0..lines.len()).filter(|&i| selected[i]).map(|i| tokens[i].unwrap_or(0)).sum();
+        let mut screen: Vec<String> = vec![format!("{HELP}    {count} files, ~{} tokens", dump::thousands(total)), String::new()];
+        for (i, line) in lines.iter().enumerate().skip(scroll).take(visible) {
+            let all: bool = line.files.iter().all(|&file| selected[file]);
+            let any: bool = line.files.iter().any(|&file| selected[file]);
+            let mark: &str = match (all && any, any) {
+                (true, _) => "[x] ",
+                (false, true) => "[-] ",
+                (false, false) => "[ ] ",
+            };
+            let text: String = crate::style::truncate(&format!("{mark}{}", line.text), width);
+            screen.push(if i == cursor { format!("\x1b[7m{text}\x1b[0m") } else { text });
+        }
+        terminal.draw(&screen)?;
+
+        match terminal.read_key()? {
+            Key::Up | Key::Char('k') => cursor = cursor.saturating_sub(1),
+            Key::Down | Key::Char('j') => cursor = (cursor + 1).min(lines.len() - 1),
+            Key::PageUp => cursor = cursor.saturating_sub(visible),
+            Key::PageDown => cursor = (cursor + visible).min(lines.len() - 1),
+            Key::Home => cursor = 0,
+            Key::End => cursor = lines.len() - 1,
+            Key::Char(' ') => {
+                let files: Vec<usize> = lines[cursor].files.clone();
+                let on: bool = !files.iter().all(|&file| selected[file]);
+                select(&lines, &files, on, &mut selected, &mut tokens);
+            }
+            Key::Char('a') => {
+                let files: Vec<usize> = (0..lines.len()).filter(|&i| !lines[i].is_dir).collect();
+                select(&lines, &files, true, &mut selected, &mut tokens);
+            }
+            Key::Char('n') => selected.fill(false),
+            Key::Enter => break,
+            Key::Escape | Key::Char('q') => return Ok(false),
+            _ => {}
+        }
+    }
+    drop(terminal);
+
+    let keep: HashSet<&str> = (0..lines.len()).filter(|&i| selected[i]).map(|i| lines[i].path.as_str()).collect();
+    retain(entries, &keep);
+    Ok(true)
+}
+
+/// Files only get read once they're selected
+fn select(lines: &[Line], files: &[usize], on: bool, selected: &mut [bool], tokens: &mut [Option<usize>]) {
+    for &file in files {
+        selected[file] = on;
+        if on && tokens[file].is_none() {
+            tokens[file] = Some(match FileText::read(Path::new(&lines[file].path)) {
+                FileText::Text(contents) => dump::estimate_tokens(&contents),
+                _ => 0,
+            });
+        }
+    }
+}
+
+/// Adds the lines for `entries`, returning the file lines among them
+fn flatten(entries: &[Entry], lines: &mut Vec<Line>) -> Vec<usize> {
+    let mut files: Vec<usize> = Vec::new();
+    for entry in entries {
+        let index: usize = lines.len();
+        lines.push(Line { text: String::new(), path: entry.path.clone(), is_dir: entry.is_dir, files: Vec::new() });
+        let below: Vec<usize> = if entry.is_dir { flatten(&entry.children, lines) } else { vec![index] };
+        files.extend(&below);
+        lines[index].files = below;
+    }
+    files
+}
+
+/// Keeps the picked files and the directories leading to them
+fn retain(entries: &mut Vec<Entry>, keep: &HashSet<&str>) {
+    entries.retain_mut(|entry| {
+        if entry.is_dir {
+            retain(&mut entry.children, keep);
+            !entry.children.is_empty()
+        } else {
+            keep.contains(entry.path.as_str())
+        }
+    });
+}
@@ -0,0 +1,147 @@
+//! Platform specific file metadata: identities, link detection and special file types.
+
+use std::fs;
+use std::path::Path;
+#[cfg(not(unix))]
+use std::path::PathBuf;
+
+/// Identifies a directory regardless of the path it was reached through
+#[cfg(unix)]
+pub type FileId = (u64, u64);
+#[cfg(not(unix))]
+pub type FileId = PathBuf;
+
+#[cfg(unix)]
+pub fn file_id(path: &Path) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata: fs::Metadata = fs::metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+pub fn file_id(path: &Path) -> Option<FileId> {
+    fs::canonicalize(path).ok()
+}
+
+/// Device `path` lives on, for staying on one filesystem
+#[cfg(unix)]
+pub fn device_id(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+pub fn device_id(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Disk space taken by a file, in 512 byte blocks like `du`
+#[cfg(unix)]
+pub fn allocated_size(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.blocks() * 512)
+}
+
+/// Only the length is known here
+#[cfg(not(unix))]
+pub fn allocated_size(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Serial number of the volume the current directory is on, as `tree` prints it in its header
+#[cfg(windows)]
+pub fn volume_serial() -> Option<u32> {
+    extern "system" {
+        fn GetVolumeInformationW(root: *const u16, name: *mut u16, name_size: u32, serial: *mut u32, max_component: *mut u32, flags: *mut u32, fs_name: *mut u16, fs_name_size: u32) -> i32;
+    }
+    let mut serial: u32 = 0;
+    //SAFETY: a null root means the current directory's volume, and serial is the only buffer asked for
+    let ok: bool = unsafe {
+        GetVolumeInformationW(std::ptr::null(), std::ptr::null_mut(), 0, &mut serial, std::ptr::null_mut(), std::ptr::null_mut(), std::ptr::null_mut(), 0)
+    } != 0;
+    ok.then_some(serial)
+}
+
+/// The device number stands in for the serial elsewhere
+#[cfg(not(windows))]
+pub fn volume_serial() -> Option<u32> {
+    device_id(Path::new(".")).map(|id| id as u32)
+}
+
+/// Like `file_id`, but only for regular files with more than one link
+#[cfg(unix)]
+pub fn hardlink_id(path: &Path) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata: fs::Metadata = fs::symlink_metadata(path).ok()?;
+    (metadata.is_file() && metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+pub fn hardlink_id(_path: &Path) -> Option<FileId> {
+    None
+}
+
+/// Annotation for FIFOs, sockets and devices. Content based features should stick to `is_file()` so they never open these
+#[cfg(unix)]
+pub fn special_kind(path: &Path) -> Option<&'static str> {
+    use std::os::unix::fs::FileTypeExt;
+    let file_type: fs::FileType = fs::metadata(path).ok()?.file_type();
+    if file_type.is_fifo() { Some("fifo") }
+    else if file_type.is_socket() { Some("socket") }
+    else if file_type.is_block_device() { Some("block device") }
+    else if file_type.is_char_device() { Some("char device") }
+    else { None }
+}
+
+#[cfg(not(unix))]
+pub fn special_kind(_path: &Path) -> Option<&'static str> {
+    None
+}
+
+/// Any of the execute bits set
+#[cfg(unix)]
+pub fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+}
+
+/// Windows goes by extension, there's no bit to look at
+#[cfg(not(unix))]
+pub fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Whether `path` is a link that shouldn't be descended into by default
+#[cfg(not(windows))]
+pub fn is_link(path: &Path) -> bool {
+    path.is_symlink()
+}
+
+/// On Windows this includes junctions and other reparse points, `C:\Users\...\Application Data` style junctions loop forever
+#[cfg(windows)]
+pub fn is_link(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0)
+}
+
+/// Columns of the terminal stdout is connected to, or `$COLUMNS`
+#[cfg(unix)]
+pub fn terminal_width() -> Option<usize> {
+    //SAFETY: TIOCGWINSZ only writes into the winsize we pass
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let ok: bool = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+    if ok && size.ws_col > 0 {
+        return Some(size.ws_col as usize);
+    }
+    columns_var()
+}
+
+#[cfg(not(unix))]
+pub fn terminal_width() -> Option<usize> {
+    columns_var()
+}
+
+fn columns_var() -> Option<usize> {
+    std::env::var("COLUMNS").ok()?.parse().ok().filter(|&columns| columns > 0)
+}
@@ -0,0 +1,195 @@
+//! `project-tree remote`, listing a hosted repository through its web API instead of cloning it.
+//!
+//! GitHub, GitLab (including self-hosted) and Bitbucket are supported, each a `Provider`.
+//! Requests go through `curl`, which every platform we run on ships, rather than pulling in an HTTP
+//! and TLS stack for one call.
+
+use crate::json::Json;
+use crate::listing::Listed;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// `github.com/owner/repo[@ref]`, with or without `https://` and `.git`. GitLab owners can be nested groups
+pub struct RepoSpec {
+    pub host: String,
+    pub owner: String,
+    pub name: String,
+    /// Branch, tag or commit, the default branch if not given
+    pub reference: Option<String>,
+}
+
+impl RepoSpec {
+    pub fn parse(spec: &str) -> Result<RepoSpec, String> {
+        let spec: &str = spec.trim_start_matches("https://").trim_start_matches("http://");
+        let (location, reference) = match spec.split_once('@') {
+            Some((location, reference)) => (location, Some(reference.to_owned())),
+            None => (spec, None),
+        };
+        let segments: Vec<&str> = location.trim_end_matches('/').trim_end_matches(".git").split('/').collect();
+        match &segments[..] {
+            [host, owner @ .., name] if !owner.is_empty() && owner.iter().chain([name]).all(|segment| !segment.is_empty()) => Ok(RepoSpec {
+                host: host.to_string(),
+                owner: owner.join("/"),
+                name: name.to_string(),
+                reference,
+            }),
+            _ => Err(format!("expected host/owner/repo[@ref], got {spec}")),
+        }
+    }
+}
+
+/// A code host's API. Adding one is implementing this and listing it in `PROVIDERS`
+pub trait Provider {
+    fn handles(&self, host: &str) -> bool;
+
+    /// Every file and directory in the repository
+    fn list(&self, spec: &RepoSpec) -> io::Result<Vec<Listed>>;
+}
+
+const PROVIDERS: &[&dyn Provider] = &[&GitHub, &GitLab, &Bitbucket];
+
+/// Every file and directory in the repository, through the provider for its host
+pub fn list(spec: &RepoSpec) -> io::Result<Vec<Listed>> {
+    match PROVIDERS.iter().find(|provider| provider.handles(&spec.host)) {
+        Some(provider) => provider.list(spec),
+        None => Err(io::Error::new(io::ErrorKind::Unsupported, format!("no API support for {}", spec.host))),
+    }
+}
+
+struct GitHub;
+
+impl Provider for GitHub {
+    fn handles(&self, host: &str) -> bool {
+        host == "github.com"
+    }
+
+    /// One recursive git trees call. `$GITHUB_TOKEN` raises the rate limit and gives access to private repositories
+    fn list(&self, spec: &RepoSpec) -> io::Result<Vec<Listed>> {
+        let reference: &str = spec.reference.as_deref().unwrap_or("HEAD");
+        let url: String = format!("https://api.github.com/repos/{}/{}/git/trees/{reference}?recursive=1", spec.owner, spec.name);
+        let mut headers: Vec<String> = vec![String::from("Accept: application/vnd.github+json")];
+        if let Some(token) = token("GITHUB_TOKEN") {
+            headers.push(format!("Authorization: Bearer {token}"));
+        }
+
+        let response: Json = fetch_json(&url, &headers)?;
+        if response.get("truncated") == Some(&Json::Bool(true)) {
+            eprintln!("warning: the repository is too big for one request, GitHub only returned part of the tree");
+        }
+        let items: &[Json] = response.get("tree").and_then(Json::as_array).ok_or_else(|| invalid(&url, "no tree in the response"))?;
+        Ok(items.iter().filter_map(|item| {
+            Some(Listed {
+                path: item.get("path")?.as_str()?.to_owned(),
+                is_dir: item.get("type")?.as_str()? == "tree",
+                size: item.get("size").and_then(Json::as_number).unwrap_or(0.0) as u64,
+            })
+        }).collect())
+    }
+}
+
+/// gitlab.com and self-hosted instances with gitlab in the host name
+struct GitLab;
+
+impl Provider for GitLab {
+    fn handles(&self, host: &str) -> bool {
+        host.contains("gitlab")
+    }
+
+    /// The repository tree endpoint, a page at a time. It has no sizes. `$GITLAB_TOKEN` for private projects
+    fn list(&self, spec: &RepoSpec) -> io::Result<Vec<Listed>> {
+        const PER_PAGE: usize = 100;
+
+        let project: String = encode(&format!("{}/{}", spec.owner, spec.name));
+        let reference: String = spec.reference.as_deref().map(|reference| format!("&ref={}", encode(reference))).unwrap_or_default();
+        let headers: Vec<String> = token("GITLAB_TOKEN").map(|token| format!("PRIVATE-TOKEN: {token}")).into_iter().collect();
+
+        let mut listed: Vec<Listed> = Vec::new();
+        for page in 1.. {
+            let url: String = format!(
+                "https://{}/api/v4/projects/{project}/repository/tree?recursive=true&per_page={PER_PAGE}&page={page}{reference}",
+                spec.host,
+            );
+            let response: Json = fetch_json(&url, &headers)?;
+            let items: &[Json] = response.as_array().ok_or_else(|| invalid(&url, "expected a list of entries"))?;
+            listed.extend(items.iter().filter_map(|item| {
+                Some(Listed {
+                    path: item.get("path")?.as_str()?.to_owned(),
+                    is_dir: item.get("type")?.as_str()? == "tree",
+                    size: 0,
+                })
+            }));
+            if items.len() < PER_PAGE { break; }
+        }
+        Ok(listed)
+    }
+}
+
+struct Bitbucket;
+
+impl Provider for Bitbucket {
+    fn handles(&self, host: &str) -> bool {
+        host == "bitbucket.org"
+    }
+
+    /// The src endpoint with a deep `max_depth`, following the `next` links. `$BITBUCKET_TOKEN` (an access token) for private repositories
+    fn list(&self, spec: &RepoSpec) -> io::Result<Vec<Listed>> {
+        //Without a ref the src endpoint redirects to the main branch
+        let reference: String = spec.reference.as_deref().map(|reference| format!("{}/", encode(reference))).unwrap_or_default();
+        let headers: Vec<String> = token("BITBUCKET_TOKEN").map(|token| format!("Authorization: Bearer {token}")).into_iter().collect();
+
+        let mut listed: Vec<Listed> = Vec::new();
+        let mut next: Option<String> = Some(format!(
+            "https://api.bitbucket.org/2.0/repositories/{}/{}/src/{reference}?max_depth=100&pagelen=100",
+            spec.owner, spec.name,
+        ));
+        while let Some(url) = next {
+            let response: Json = fetch_json(&url, &headers)?;
+            let items: &[Json] = response.get("values").and_then(Json::as_array).ok_or_else(|| invalid(&url, "no values in the response"))?;
+            listed.extend(items.iter().filter_map(|item| {
+                Some(Listed {
+                    path: item.get("path")?.as_str()?.to_owned(),
+                    is_dir: item.get("type")?.as_str()? == "commit_directory",
+                    size: item.get("size").and_then(Json::as_number).unwrap_or(0.0) as u64,
+                })
+            }));
+            next = response.get("next").and_then(Json::as_str).map(str::to_owned);
+        }
+        Ok(listed)
+    }
+}
+
+fn token(variable: &str) -> Option<String> {
+    std::env::var(variable).ok().map(|token| token.trim().to_owned()).filter(|token| !token.is_empty())
+}
+
+/// Percent encodes everything but unreserved characters, `/` included
+fn encode(text: &str) -> String {
+    text.bytes().map(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+        _ => format!("%{byte:02X}"),
+    }).collect()
+}
+
+/// The headers go to curl on stdin, on its command line anyone on the machine could read the tokens in them with `ps`
+fn fetch_json(url: &str, headers: &[String]) -> io::Result<Json> {
+    let mut curl = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location", "--header", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| io::Error::new(err.kind(), format!("could not run curl: {err}")))?;
+    let mut stdin = curl.stdin.take().ok_or_else(|| invalid(url, "curl has no stdin"))?;
+    stdin.write_all(headers.iter().map(|header| format!("{header}\n")).collect::<String>().as_bytes())?;
+    //curl reads the headers up to the end of its input
+    drop(stdin);
+    let output = curl.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::new(io::ErrorKind::Other, format!("{url}: {}", String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    Json::parse(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| invalid(url, "response isn't JSON"))
+}
+
+fn invalid(url: &str, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{url}: {message}"))
+}
@@ -0,0 +1,77 @@
+//! Links to entries on the repository's web UI, from `git remote get-url origin` and the current branch.
+
+use std::process::Command;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Host {
+    GitHub,
+    GitLab,
+    Bitbucket,
+}
+
+#[derive(Clone, Debug)]
+pub struct RepoLinks {
+    host: Host,
+    /// `https://github.com/owner/repo`
+    base: String,
+    branch: String,
+    /// Where the scan root is inside the repository, `sub/dir/` or empty
+    prefix: String,
+}
+
+impl RepoLinks {
+    /// Asks git about the repository the current directory is in
+    pub fn detect() -> Result<RepoLinks, String> {
+        let remote: String = git(&["remote", "get-url", "origin"])?;
+        let base: String = web_url(&remote).ok_or_else(|| format!("don't know how to link to remote {remote}"))?;
+        let host: Host = if base.contains("gitlab") {
+            Host::GitLab
+        } else if base.contains("bitbucket") {
+            Host::Bitbucket
+        } else {
+            Host::GitHub
+        };
+        Ok(RepoLinks {
+            host,
+            base,
+            branch: git(&["rev-parse", "--abbrev-ref", "HEAD"])?,
+            prefix: git(&["rev-parse", "--show-prefix"])?,
+        })
+    }
+
+    /// Web URL of the root relative `path`
+    pub fn url(&self, path: &str, is_dir: bool) -> String {
+        let kind: &str = match (self.host, is_dir) {
+            (Host::GitHub, false) => "blob",
+            (Host::GitHub, true) => "tree",
+            (Host::GitLab, false) => "-/blob",
+            (Host::GitLab, true) => "-/tree",
+            (Host::Bitbucket, _) => "src",
+        };
+        let path: String = format!("{}{path}", self.prefix).replace(' ', "%20");
+        format!("{}/{kind}/{}/{path}", self.base, self.branch)
+    }
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git").args(args).output().map_err(|err| format!("could not run git: {err}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// `git@github.com:owner/repo.git`, `ssh://git@host/owner/repo` and `https://user@host/owner/repo.git` to `https://host/owner/repo`
+fn web_url(remote: &str) -> Option<String> {
+    let remote: &str = remote.trim_end_matches('/').trim_end_matches(".git");
+    let (host, path) = if let Some((_, rest)) = remote.split_once("://") {
+        rest.split_once('/')?
+    } else {
+        //scp like syntax
+        remote.split_once(':')?
+    };
+    //Drop credentials and ports
+    let host: &str = host.rsplit('@').next()?;
+    let host: &str = host.split(':').next()?;
+    Some(format!("https://{host}/{}", path.trim_start_matches('/')))
+}
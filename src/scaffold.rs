@@ -0,0 +1,171 @@
+//! `project-tree scaffold`, creating the directories and empty files of a tree pasted from a design doc.
+//!
+//! Reads the text tree (ours or GNU tree's, any charset), the markdown list, or the JSON format.
+
+use crate::json::Json;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// An entry read back from a rendered tree
+pub struct Node {
+    /// Name, or a path from the root when the tree was rendered with --full-path
+    pub name: String,
+    pub is_dir: bool,
+    pub children: Vec<Node>,
+}
+
+/// Guide cells a line can start with, each one level deeper
+const GUIDES: &[&str] = &[
+    "│   ", "├── ", "└── ", "    ", "|   ", "|-- ", "`-- ", "+-- ", "\\-- ", "╰── ", "║   ", "╠══ ", "╚══ ", "┃   ", "┣━━ ", "┗━━ ",
+];
+
+/// Reads whichever format `text` is in
+pub fn parse(text: &str) -> Result<Vec<Node>, String> {
+    match text.trim_start().chars().next() {
+        Some('[') | Some('{') => parse_json(text),
+        Some('-') if text.trim_start().starts_with("- ") => Ok(parse_lines(text, markdown_line)),
+        _ => Ok(parse_lines(text, text_line)),
+    }
+}
+
+/// Depth and label of an ascii tree line. GNU tree pads its guides with non-breaking spaces
+fn text_line(line: &str) -> Option<(usize, String)> {
+    let line: String = line.replace('\u{a0}', " ");
+    let mut rest: &str = &line;
+    let mut depth: usize = 0;
+    while let Some(guide) = GUIDES.iter().find(|guide| rest.starts_with(*guide)) {
+        rest = &rest[guide.len()..];
+        depth += 1;
+    }
+    //The root line of GNU tree
+    if depth == 0 && rest.trim() == "." { return None; }
+    Some((depth, rest.to_owned()))
+}
+
+fn markdown_line(line: &str) -> Option<(usize, String)> {
+    let indent: usize = line.len() - line.trim_start().len();
+    let item: &str = line.trim_start().strip_prefix("- ")?;
+    //Names are in backticks, possibly wrapped in a link
+    let name: &str = item.split('`').nth(1).unwrap_or(item);
+    Some((indent / 2, name.to_owned()))
+}
+
+/// Builds the nodes from (depth, label) lines, a line's parent being the closest one above it that is less deep.
+/// The tree ends at the first blank line, where the summaries start
+fn parse_lines(text: &str, parse_line: fn(&str) -> Option<(usize, String)>) -> Vec<Node> {
+    let mut roots: Vec<Node> = Vec::new();
+    let mut stack: Vec<(usize, Node)> = Vec::new();
+    let attach = |node: Node, stack: &mut Vec<(usize, Node)>, roots: &mut Vec<Node>| match stack.last_mut() {
+        Some((_, parent)) => {
+            parent.is_dir = true;
+            parent.children.push(node);
+        }
+        None => roots.push(node),
+    };
+
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if roots.is_empty() && stack.is_empty() { continue; }
+            break;
+        }
+        if is_summary(line) { continue; }
+        let Some((depth, label)) = parse_line(line) else { continue };
+        let Some((name, is_dir)) = clean_label(&label) else { continue };
+
+        while stack.last().is_some_and(|(top, _)| *top >= depth) {
+            let (_, node) = stack.pop().expect("checked above");
+            attach(node, &mut stack, &mut roots);
+        }
+        stack.push((depth, Node { name, is_dir, children: Vec::new() }));
+    }
+    while let Some((_, node)) = stack.pop() {
+        attach(node, &mut stack, &mut roots);
+    }
+    roots
+}
+
+/// `3 directories, 5 files` from GNU tree, and our own interruption marker
+fn is_summary(line: &str) -> bool {
+    let line: &str = line.trim();
+    line.starts_with("[interrupted") || (line.ends_with(" files") || line.ends_with(" file")) && line.contains(" director")
+}
+
+/// Name without the notes after it, and whether it's marked as a directory
+fn clean_label(label: &str) -> Option<(String, bool)> {
+    let mut name: &str = label.trim();
+    for separator in [" -> ", " => ", " — "] {
+        if let Some((before, _)) = name.split_once(separator) { name = before; }
+    }
+    //Notes in brackets, `[fifo]`, `[TODO 2]`, ...
+    while name.ends_with(']') {
+        let Some(start) = name.rfind(" [") else { break };
+        name = name[..start].trim_end();
+    }
+    let is_dir: bool = name.ends_with('/');
+    let name: &str = name.trim_end_matches('/');
+    (!name.is_empty()).then(|| (name.to_owned(), is_dir))
+}
+
+fn parse_json(text: &str) -> Result<Vec<Node>, String> {
+    let json: Json = Json::parse(text).ok_or("not valid JSON")?;
+    match &json {
+        Json::Array(items) => items.iter().map(json_node).collect(),
+        //A rendering with --root
+        Json::Object(_) => Ok(json_node(&json)?.children),
+        _ => Err(String::from("expected an array or object of entries")),
+    }
+}
+
+fn json_node(json: &Json) -> Result<Node, String> {
+    let name: &str = json.get("name").and_then(Json::as_str).ok_or("entry without a name")?;
+    let is_dir: bool = json.get("type").and_then(Json::as_str) == Some("directory");
+    let children: Vec<Node> = match json.get("children").and_then(Json::as_array) {
+        Some(children) => children.iter().map(json_node).collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+    Ok(Node { name: name.to_owned(), is_dir, children })
+}
+
+/// What scaffolding created
+#[derive(Default)]
+pub struct Created {
+    pub dirs: usize,
+    pub files: usize,
+    /// Files that already existed and were left alone
+    pub skipped: usize,
+}
+
+/// Creates `nodes` under `root`. Existing files are never overwritten
+pub fn create(nodes: &[Node], root: &Path) -> io::Result<Created> {
+    let mut created: Created = Created::default();
+    create_level(nodes, root, root, &mut created)?;
+    Ok(created)
+}
+
+fn create_level(nodes: &[Node], root: &Path, parent: &Path, created: &mut Created) -> io::Result<()> {
+    for node in nodes {
+        let relative: &Path = Path::new(&node.name);
+        //A pasted tree shouldn't be able to write outside the target
+        if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("refusing to create {}", node.name)));
+        }
+        let path: PathBuf = if node.name.contains('/') { root.join(relative) } else { parent.join(relative) };
+
+        if node.is_dir {
+            if !path.is_dir() {
+                fs::create_dir_all(&path)?;
+                created.dirs += 1;
+            }
+            create_level(&node.children, root, &path, created)?;
+        } else {
+            if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => created.files += 1,
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => created.skipped += 1,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+    Ok(())
+}
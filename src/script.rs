@@ -0,0 +1,54 @@
+//! `--emit-script`, shell commands recreating the scanned layout with empty files.
+
+use crate::entry::Entry;
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    /// POSIX shell, mkdir -p and touch
+    Sh,
+    /// PowerShell, New-Item
+    Ps1,
+}
+
+impl Shell {
+    /// Script creating every directory and (empty) file, parents before children
+    pub fn script(self, entries: &[Entry]) -> String {
+        let mut lines: Vec<String> = match self {
+            Shell::Sh => vec![String::from("#!/bin/sh"), String::from("set -e")],
+            Shell::Ps1 => vec![String::from("$ErrorActionPreference = 'Stop'")],
+        };
+        self.commands(entries, &mut lines);
+        lines.join("\n")
+    }
+
+    /// For markdown code fences
+    pub fn fence_language(self) -> &'static str {
+        match self {
+            Shell::Sh => "sh",
+            Shell::Ps1 => "powershell",
+        }
+    }
+
+    fn commands(self, entries: &[Entry], lines: &mut Vec<String>) {
+        for entry in entries {
+            let path: String = self.quote(&entry.path);
+            lines.push(match (self, entry.is_dir) {
+                (Shell::Sh, true) => format!("mkdir -p {path}"),
+                (Shell::Sh, false) => format!("touch {path}"),
+                (Shell::Ps1, true) => format!("New-Item -ItemType Directory -Force -Path {path} | Out-Null"),
+                (Shell::Ps1, false) => format!("New-Item -ItemType File -Force -Path {path} | Out-Null"),
+            });
+            self.commands(&entry.children, lines);
+        }
+    }
+
+    /// Single quoted, the shells only differ in how a quote inside is escaped
+    fn quote(self, path: &str) -> String {
+        let escaped: String = match self {
+            Shell::Sh => path.replace('\'', r"'\''"),
+            Shell::Ps1 => path.replace('\'', "''"),
+        };
+        format!("'./{escaped}'")
+    }
+}
@@ -0,0 +1,78 @@
+//! `project-tree serve`, the tree over HTTP for dashboards, rescanned on every request.
+//!
+//! `/` is a small HTML page, `/tree.json` and `/tree.txt` the JSON and text formats.
+//!
+//! Every connection gets a thread, so a slow client doesn't hold up the others, while the scans themselves take turns.
+
+use crate::format::Format;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+/// Clients that send nothing for this long are dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Request line and headers together
+const MAX_REQUEST: u64 = 64 * 1024;
+
+/// Serves until killed, calling `render` for a fresh tree on each request
+pub fn run(host: &str, port: u16, render: impl FnMut(Format) -> io::Result<String> + Send) -> io::Result<()> {
+    let listener: TcpListener = TcpListener::bind((host, port))?;
+    eprintln!("serving on http://{}", listener.local_addr()?);
+    let render = Mutex::new(render);
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let render = &render;
+            scope.spawn(move || {
+                //One bad client shouldn't take the server down
+                if let Err(err) = stream.and_then(|stream| respond(stream, render)) {
+                    eprintln!("warning: {err}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, render: &Mutex<impl FnMut(Format) -> io::Result<String>>) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST));
+    let mut request_line: String = String::new();
+    reader.read_line(&mut request_line)?;
+    //The headers aren't needed, but they have to be read: closing with unread data makes the client see a reset
+    loop {
+        let mut header: String = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim_end_matches(['\r', '\n']).is_empty() {
+            break;
+        }
+    }
+    let render = |format: Format| -> io::Result<String> { render.lock().unwrap_or_else(PoisonError::into_inner)(format) };
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let path: &str = target.split('?').next().unwrap_or_default();
+
+    let (status, content_type, body): (&str, &str, String) = match (method, path) {
+        ("GET", "/") => ("200 OK", "text/html; charset=utf-8", page(&render(Format::Text)?)),
+        ("GET", "/tree.json") => ("200 OK", "application/json", render(Format::Json)?),
+        ("GET", "/tree.txt") => ("200 OK", "text/plain; charset=utf-8", render(Format::Text)?),
+        ("GET", _) => ("404 Not Found", "text/plain; charset=utf-8", String::from("not found")),
+        _ => ("405 Method Not Allowed", "text/plain; charset=utf-8", String::from("only GET is supported")),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{body}",
+        body.len(),
+    )?;
+    stream.flush()
+}
+
+fn page(tree: &str) -> String {
+    let escaped: String = crate::format::html_escape(tree);
+    format!(
+        "<!doctype html>\n<meta charset=\"utf-8\">\n<title>project-tree</title>\n\
+         <pre style=\"font-family: monospace; line-height: 1.2\">{escaped}</pre>\n\
+         <p><a href=\"/tree.json\">JSON</a> · <a href=\"/tree.txt\">text</a></p>\n"
+    )
+}
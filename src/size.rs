@@ -0,0 +1,45 @@
+//! File sizes, du style: a directory is the sum of everything below it, and a file is the disk space allocated to it
+//! unless the apparent size (its length) is asked for. Sparse files take less than their length, small files more.
+
+use crate::platform;
+use std::fs;
+use std::path::Path;
+
+/// Size of a file or symlink itself, links aren't followed
+pub fn of_file(path: &Path, apparent: bool) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else { return 0 };
+    if apparent {
+        return metadata.len();
+    }
+    platform::allocated_size(&metadata).unwrap_or(metadata.len())
+}
+
+/// Total size of everything below a directory that wasn't scanned (stopped, other filesystem), skipping what can't be read
+pub fn of_dir(path: &Path, apparent: bool) -> u64 {
+    let Ok(read_dir) = fs::read_dir(path) else { return 0 };
+    read_dir.filter_map(Result::ok).map(|entry| {
+        let path = entry.path();
+        //file_type() doesn't follow symlinks, so linked directories can't loop us
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => of_dir(&path, apparent),
+            _ => of_file(&path, apparent),
+        }
+    }).sum()
+}
+
+/// `123B`, `4.0K`, `12M`, powers of 1024 like `du -h`
+pub fn human(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value: f64 = bytes as f64;
+    let mut unit: &str = "B";
+    for next in UNITS {
+        if value < 1024.0 { break; }
+        value /= 1024.0;
+        unit = next;
+    }
+    if value < 9.95 { format!("{value:.1}{unit}") } else { format!("{value:.0}{unit}") }
+}
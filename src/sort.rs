@@ -0,0 +1,28 @@
+//! `--sort`, reordering the scanned tree by something other than names.
+
+use crate::entry::Entry;
+use crate::stats;
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+    /// By name, in the --collate order (directories first with --dirs)
+    #[default]
+    Name,
+    /// Directories with the most entries below them first
+    Count,
+    /// Directories first, then files grouped by extension
+    Ext,
+}
+
+/// Reorders every level of `entries`. Sorts are stable, so ties keep the name order
+pub fn apply(entries: &mut [Entry], by: SortBy) {
+    match by {
+        SortBy::Name => return,
+        SortBy::Count => entries.sort_by_cached_key(|entry| std::cmp::Reverse(entry.count())),
+        SortBy::Ext => entries.sort_by_cached_key(|entry| (!entry.is_dir, if entry.is_dir { String::new() } else { stats::extension(&entry.name) })),
+    }
+    for entry in entries {
+        apply(&mut entry.children, by);
+    }
+}
@@ -0,0 +1,99 @@
+//! Summary tables about the scanned tree, printed after it with --stats.
+
+use crate::entry::{for_each_file, Entry};
+use crate::size;
+use std::collections::HashMap;
+
+/// File count and total size per extension, biggest groups first, with everything past `top` lumped into "other"
+pub fn extension_table(entries: &[Entry], top: usize) -> String {
+    let mut groups: HashMap<String, (usize, u64)> = HashMap::new();
+    for_each_file(entries, &mut |entry| {
+        let (files, bytes) = groups.entry(extension(&entry.name)).or_default();
+        *files += 1;
+        *bytes += entry.size;
+    });
+
+    let mut rows: Vec<(String, usize, u64)> = groups.into_iter().map(|(extension, (files, bytes))| (extension, files, bytes)).collect();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+
+    let total: (usize, u64) = rows.iter().fold((0, 0), |(files, bytes), row| (files + row.1, bytes + row.2));
+    if rows.len() > top {
+        let other: (usize, u64) = rows.drain(top..).fold((0, 0), |(files, bytes), row| (files + row.1, bytes + row.2));
+        rows.push((String::from("other"), other.0, other.1));
+    }
+    rows.push((String::from("Total"), total.0, total.1));
+
+    let mut table: String = format!("{:<12} {:>7} {:>9}", "Extension", "Files", "Size");
+    for (extension, files, bytes) in rows {
+        table.push_str(&format!("\n{extension:<12} {files:>7} {:>9}", size::human(bytes)));
+    }
+    table
+}
+
+/// Entries per depth level (1 being the top), with the maximum and average depth of files
+pub fn depth_table(entries: &[Entry]) -> String {
+    let mut levels: Vec<usize> = Vec::new();
+    let mut file_depths: (usize, usize) = (0, 0);
+    count_depths(entries, 1, &mut levels, &mut file_depths);
+
+    let average: f64 = if file_depths.0 == 0 { 0.0 } else { file_depths.1 as f64 / file_depths.0 as f64 };
+    let mut table: String = format!("Max depth {}, average file depth {average:.1}", levels.len());
+    table.push_str(&format!("\n{:<12} {:>7}", "Depth", "Entries"));
+    for (i, count) in levels.iter().enumerate() {
+        table.push_str(&format!("\n{:<12} {count:>7}", i + 1));
+    }
+    table
+}
+
+/// Fills in the entry count per level and the (count, sum) of file depths
+fn count_depths(entries: &[Entry], depth: usize, levels: &mut Vec<usize>, file_depths: &mut (usize, usize)) {
+    if entries.is_empty() { return; }
+    if levels.len() < depth {
+        levels.push(0);
+    }
+    for entry in entries {
+        levels[depth - 1] += 1;
+        if !entry.is_dir {
+            file_depths.0 += 1;
+            file_depths.1 += depth;
+        }
+        count_depths(&entry.children, depth + 1, levels, file_depths);
+    }
+}
+
+/// File counts per size bucket, from <1K up to >100M
+pub fn size_histogram(entries: &[Entry]) -> String {
+    const BUCKETS: [(&str, u64); 7] = [
+        ("<1K", 1 << 10),
+        ("1K-10K", 10 << 10),
+        ("10K-100K", 100 << 10),
+        ("100K-1M", 1 << 20),
+        ("1M-10M", 10 << 20),
+        ("10M-100M", 100 << 20),
+        (">100M", u64::MAX),
+    ];
+    const BAR_WIDTH: usize = 30;
+
+    let mut counts: [usize; BUCKETS.len()] = [0; BUCKETS.len()];
+    for_each_file(entries, &mut |entry| {
+        let bucket: usize = BUCKETS.iter().position(|&(_, limit)| entry.size < limit).unwrap_or(BUCKETS.len() - 1);
+        counts[bucket] += 1;
+    });
+
+    let most: usize = counts.iter().copied().max().unwrap_or(0).max(1);
+    let mut table: String = format!("{:<12} {:>7}", "Size", "Files");
+    for ((label, _), count) in BUCKETS.iter().zip(counts) {
+        //Round up so a bucket with anything in it never looks empty
+        let bar: String = "█".repeat((count * BAR_WIDTH).div_ceil(most));
+        table.push_str(format!("\n{label:<12} {count:>7} {bar}").trim_end());
+    }
+    table
+}
+
+/// `.rs`, or `(none)` for files without one. Dotfiles like `.gitignore` have no extension
+pub fn extension(name: &str) -> String {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => name[dot..].to_ascii_lowercase(),
+        _ => String::from("(none)"),
+    }
+}
@@ -0,0 +1,385 @@
+//! ANSI styling for entries that need to stand out, and OSC 8 hyperlinks.
+
+use clap::ValueEnum;
+use std::borrow::Cow;
+
+const RESET: &str = "\x1b[0m";
+
+/// How an entry is painted: a foreground color and text attributes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    /// One of the 8 standard colors, 0 black to 7 white
+    Basic(u8),
+    /// The bright variant of a standard color
+    Bright(u8),
+    /// 256 color palette index
+    Fixed(u8),
+    Rgb(u8, u8, u8),
+}
+
+const COLOR_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+impl Style {
+    const PLAIN: Style = Style { color: None, bold: false, dim: false, italic: false, underline: false };
+    pub const RED: Style = Style { color: Some(Color::Basic(1)), ..Style::PLAIN };
+    pub const DIM: Style = Style { dim: true, ..Style::PLAIN };
+
+    /// Parses space separated words like `bold yellow`, `dim`, `bright-blue`, `208` or `#ff8800`
+    pub fn parse(spec: &str) -> Result<Style, String> {
+        let mut style: Style = Style::PLAIN;
+        for word in spec.split_whitespace() {
+            let word: String = word.to_ascii_lowercase().replace('_', "-");
+            match word.as_str() {
+                "bold" => style.bold = true,
+                "dim" => style.dim = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "plain" | "none" => {}
+                word => style.color = Some(Color::parse(word).ok_or_else(|| format!("unknown color or attribute `{word}`"))?),
+            }
+        }
+        Ok(style)
+    }
+
+    /// From SGR parameters like `01;34` or `38;5;208`, as LS_COLORS has them. Backgrounds and what else a `Style`
+    /// can't hold are skipped
+    pub fn from_sgr(codes: &str) -> Style {
+        let mut style: Style = Style::PLAIN;
+        let codes: Vec<u8> = codes.split(';').filter_map(|code| code.parse().ok()).collect();
+        let mut i: usize = 0;
+        while i < codes.len() {
+            match codes[i] {
+                0 => style = Style::PLAIN,
+                1 => style.bold = true,
+                2 => style.dim = true,
+                3 => style.italic = true,
+                4 => style.underline = true,
+                n @ 30..=37 => style.color = Some(Color::Basic(n - 30)),
+                n @ 90..=97 => style.color = Some(Color::Bright(n - 90)),
+                38 | 48 => {
+                    //Extended colors take 2 or 4 more parameters, which mustn't be read as codes of their own
+                    let (color, taken): (Option<Color>, usize) = match codes.get(i + 1..) {
+                        Some([5, n, ..]) => (Some(Color::Fixed(*n)), 2),
+                        Some([2, r, g, b, ..]) => (Some(Color::Rgb(*r, *g, *b)), 4),
+                        _ => (None, 0),
+                    };
+                    if codes[i] == 38 && color.is_some() { style.color = color; }
+                    i += taken;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        style
+    }
+
+    /// With the attributes of `other` added, and its color if it has one
+    pub fn merge(self, other: Style) -> Style {
+        Style {
+            color: other.color.or(self.color),
+            bold: self.bold || other.bold,
+            dim: self.dim || other.dim,
+            italic: self.italic || other.italic,
+            underline: self.underline || other.underline,
+        }
+    }
+
+    /// SGR parameters, `1;33` for bold yellow
+    fn codes(self) -> String {
+        let mut codes: Vec<String> = Vec::new();
+        for (set, code) in [(self.bold, "1"), (self.dim, "2"), (self.italic, "3"), (self.underline, "4")] {
+            if set { codes.push(String::from(code)); }
+        }
+        match self.color {
+            Some(Color::Basic(n)) => codes.push(format!("{}", 30 + n)),
+            Some(Color::Bright(n)) => codes.push(format!("{}", 90 + n)),
+            Some(Color::Fixed(n)) => codes.push(format!("38;5;{n}")),
+            Some(Color::Rgb(r, g, b)) => codes.push(format!("38;2;{r};{g};{b}")),
+            None => {}
+        }
+        codes.join(";")
+    }
+
+    /// Inline CSS declarations, for HTML output
+    pub fn css(self) -> String {
+        self.declarations("color")
+    }
+
+    /// Inline CSS declarations for SVG text, which is colored by its fill
+    pub fn svg_css(self) -> String {
+        self.declarations("fill")
+    }
+
+    fn declarations(self, color_property: &str) -> String {
+        let mut css: Vec<String> = Vec::new();
+        if let Some(color) = self.color {
+            let (r, g, b) = color.rgb();
+            css.push(format!("{color_property}: #{r:02x}{g:02x}{b:02x}"));
+        }
+        for (set, declaration) in [(self.bold, "font-weight: bold"), (self.dim, "opacity: 0.5"), (self.italic, "font-style: italic"), (self.underline, "text-decoration: underline")] {
+            if set { css.push(String::from(declaration)); }
+        }
+        css.join("; ")
+    }
+
+    /// Wraps `text` in this style's escape codes
+    pub fn paint(self, text: &str) -> String {
+        let codes: String = self.codes();
+        if codes.is_empty() {
+            return text.to_owned();
+        }
+        format!("\x1b[{codes}m{text}{RESET}")
+    }
+}
+
+impl Color {
+    /// xterm's default palette
+    fn rgb(self) -> (u8, u8, u8) {
+        const BASIC: [(u8, u8, u8); 16] = [
+            (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+            (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+        ];
+        let cube = |level: u8| if level == 0 { 0 } else { 55 + level * 40 };
+        match self {
+            Color::Basic(n) => BASIC[n as usize % 8],
+            Color::Bright(n) => BASIC[8 + n as usize % 8],
+            Color::Fixed(n @ 0..=15) => BASIC[n as usize],
+            Color::Fixed(n @ 16..=231) => (cube((n - 16) / 36), cube((n - 16) / 6 % 6), cube((n - 16) % 6)),
+            Color::Fixed(n) => (8 + (n - 232) * 10, 8 + (n - 232) * 10, 8 + (n - 232) * 10),
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+
+    /// A color name (`red`, `bright-red`), palette index or `#rrggbb`
+    fn parse(word: &str) -> Option<Color> {
+        if let Some(hex) = word.strip_prefix('#') {
+            let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+            return (hex.len() == 6).then(|| Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))).flatten();
+        }
+        if let Ok(index) = word.parse::<u8>() {
+            return Some(Color::Fixed(index));
+        }
+        let (bright, name) = match word.strip_prefix("bright-") {
+            Some(name) => (true, name),
+            None => (false, word),
+        };
+        let n: u8 = COLOR_NAMES.iter().position(|&color| color == name)? as u8;
+        Some(if bright { Color::Bright(n) } else { Color::Basic(n) })
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorWhen {
+    /// On a terminal, unless NO_COLOR is set. CLICOLOR_FORCE turns them on when piped
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorWhen {
+    /// `is_tty` is whether the output goes to a terminal
+    pub fn enabled(self, is_tty: bool) -> bool {
+        let set = |name: &str| std::env::var_os(name).is_some_and(|value| !value.is_empty() && value != "0");
+        match self {
+            //https://no-color.org, which wins over CLICOLOR_FORCE when both are set
+            ColorWhen::Auto => !set("NO_COLOR") && (is_tty || set("CLICOLOR_FORCE")),
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Hyperlink {
+    /// When the terminal is known to support them
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl Hyperlink {
+    pub fn enabled(self) -> bool {
+        match self {
+            Hyperlink::Auto => terminal_supports_hyperlinks(),
+            Hyperlink::Always => true,
+            Hyperlink::Never => false,
+        }
+    }
+}
+
+/// There is no way to query this, so go by what the well known terminals set
+fn terminal_supports_hyperlinks() -> bool {
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+
+    let term_program: String = var("TERM_PROGRAM");
+    let vte_version: u32 = var("VTE_VERSION").parse().unwrap_or(0);
+    ["iTerm.app", "WezTerm", "vscode", "Hyper", "ghostty"].contains(&term_program.as_str())
+        || var("TERM").contains("kitty")
+        || var("TERM").starts_with("foot")
+        || vte_version >= 5000
+        || !var("WT_SESSION").is_empty()
+        || !var("KONSOLE_VERSION").is_empty()
+}
+
+/// Control characters as `\xNN`, so names can't smuggle escape sequences into the terminal or the clipboard
+pub fn escape_control(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(char::is_control) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.chars().map(|c| if c.is_control() { format!("\\x{:02x}", c as u32) } else { c.to_string() }).collect())
+}
+
+/// Wraps `text` in an OSC 8 link to `url`
+pub fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Columns `text` takes up in a terminal, skipping escape sequences and counting emoji and CJK as two
+pub fn width(text: &str) -> usize {
+    let mut width: usize = 0;
+    let mut previous: usize = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        //The emoji variation selector turns a narrow symbol like ⚙ into a wide emoji
+        if c == '\u{FE0F}' && previous == 1 {
+            width += 1;
+            previous = 2;
+            continue;
+        }
+        if c == '\x1b' {
+            match chars.next() {
+                //CSI, up to the final letter
+                Some('[') => { chars.by_ref().find(|c| c.is_ascii_alphabetic()); }
+                //OSC, up to BEL or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' { break; }
+                        if c == '\x1b' { chars.next(); break; }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        previous = char_width(c);
+        width += previous;
+    }
+    width
+}
+
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        //Combining marks, zero width spaces and joiners, variation selectors
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 | 0x1F300..=0x1FAFF | 0x20000..=0x3FFFD => 2,
+        //Symbols that are emoji by default
+        0x231A..=0x231B | 0x23E9..=0x23EC | 0x23F0 | 0x23F3 | 0x25FD..=0x25FE | 0x2614..=0x2615 | 0x2648..=0x2653
+        | 0x267F | 0x2693 | 0x26A1 | 0x26AA..=0x26AB | 0x26BD..=0x26BE | 0x26C4..=0x26C5 | 0x26CE | 0x26D4 | 0x26EA
+        | 0x26F2..=0x26F3 | 0x26F5 | 0x26FA | 0x26FD | 0x2705 | 0x270A..=0x270B | 0x2728 | 0x274C | 0x274E
+        | 0x2753..=0x2755 | 0x2757 | 0x2795..=0x2797 | 0x27B0 | 0x27BF | 0x2B1B..=0x2B1C | 0x2B50 | 0x2B55 => 2,
+        c if c < 0x20 || c == 0x7F => 0,
+        _ => 1,
+    }
+}
+
+/// Cuts `text` down to `max` columns, ending in `…`. Escape sequences past the cut are kept so colors and links still get closed
+pub fn truncate(text: &str, max: usize) -> String {
+    if width(text) <= max {
+        return text.to_owned();
+    }
+    let mut truncated: String = String::with_capacity(text.len());
+    let mut used: usize = 0;
+    let mut previous: usize = 0;
+    let mut cut: bool = max == 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            //Copy the whole sequence, same rules as width()
+            truncated.push(c);
+            match chars.next() {
+                Some('[') => {
+                    truncated.push('[');
+                    for c in chars.by_ref() {
+                        truncated.push(c);
+                        if c.is_ascii_alphabetic() { break; }
+                    }
+                }
+                Some(']') => {
+                    truncated.push(']');
+                    while let Some(c) = chars.next() {
+                        truncated.push(c);
+                        if c == '\x07' { break; }
+                        if c == '\x1b' { truncated.extend(chars.next()); break; }
+                    }
+                }
+                other => truncated.extend(other),
+            }
+            continue;
+        }
+        if cut { continue; }
+        let c_width: usize = if c == '\u{FE0F}' && previous == 1 { 1 } else { char_width(c) };
+        previous = c_width;
+        //Leave a column for the ellipsis
+        if used + c_width + 1 > max {
+            truncated.push('…');
+            cut = true;
+            continue;
+        }
+        truncated.push(c);
+        used += c_width;
+    }
+    truncated
+}
+
+/// Splits `text` after `max` columns (at least one character, so wrapping always makes progress).
+/// Escape sequences stay on the side they appear on
+pub fn split(text: &str, max: usize) -> (String, String) {
+    let mut head: String = String::with_capacity(text.len());
+    let mut used: usize = 0;
+    let mut previous: usize = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\x1b' {
+            head.push(c);
+            match chars.next() {
+                Some((_, '[')) => {
+                    head.push('[');
+                    for (_, c) in chars.by_ref() {
+                        head.push(c);
+                        if c.is_ascii_alphabetic() { break; }
+                    }
+                }
+                Some((_, ']')) => {
+                    head.push(']');
+                    while let Some((_, c)) = chars.next() {
+                        head.push(c);
+                        if c == '\x07' { break; }
+                        if c == '\x1b' { head.extend(chars.next().map(|(_, c)| c)); break; }
+                    }
+                }
+                other => head.extend(other.map(|(_, c)| c)),
+            }
+            continue;
+        }
+        let c_width: usize = if c == '\u{FE0F}' && previous == 1 { 1 } else { char_width(c) };
+        if used + c_width > max && used > 0 {
+            return (head, text[i..].to_owned());
+        }
+        head.push(c);
+        used += c_width;
+        previous = c_width;
+    }
+    (head, String::new())
+}
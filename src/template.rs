@@ -0,0 +1,97 @@
+//! `--template` documents wrapping the tree, with `{{placeholder}}` substitution.
+
+use crate::entry::{self, Entry};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Values available to templates
+pub struct Context<'a> {
+    pub tree: &'a str,
+    pub root: &'a str,
+    pub entries: &'a [Entry],
+}
+
+/// Replaces `{{tree}}`, `{{date}}`, `{{root}}`, `{{count_files}}` and `{{count_dirs}}`, unknown placeholders are left as is.
+/// One pass over the template, so placeholders showing up in the values (a file named `{{date}}`) stay as they are
+pub fn fill(template: &str, context: &Context) -> String {
+    let (files, dirs) = entry::count_kinds(context.entries);
+    let mut filled: String = String::with_capacity(template.len() + context.tree.len());
+    let mut rest: &str = template;
+    while let Some(start) = rest.find("{{") {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let name: Option<&str> = rest[2..].find("}}").map(|end| &rest[2..2 + end]);
+        let value: Option<String> = match name {
+            Some("tree") => Some(context.tree.to_owned()),
+            Some("date") => Some(today()),
+            Some("root") => Some(context.root.to_owned()),
+            Some("count_files") => Some(files.to_string()),
+            Some("count_dirs") => Some(dirs.to_string()),
+            _ => None,
+        };
+        match (name, value) {
+            (Some(name), Some(value)) => {
+                filled.push_str(&value);
+                rest = &rest[name.len() + 4..];
+            }
+            //`{{{tree}}` is a brace and then the tree
+            _ => {
+                filled.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Today's UTC date as YYYY-MM-DD
+fn today() -> String {
+    let days: i64 = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64 / 86400);
+    let (year, month, day) = civil_from_days(days);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Howard Hinnant's days -> proleptic gregorian date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z: i64 = days + 719468;
+    let era: i64 = z.div_euclid(146097);
+    let day_of_era: i64 = z.rem_euclid(146097);
+    let year_of_era: i64 = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp: i64 = (5 * day_of_year + 2) / 153;
+    let day: u32 = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month: u32 = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year: i64 = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context<'a>(tree: &'a str, root: &'a str) -> Context<'a> {
+        Context { tree, root, entries: &[] }
+    }
+
+    #[test]
+    fn fills_placeholders() {
+        assert_eq!(fill("# {{root}}\n{{tree}}\n{{count_files}} files", &context("a.rs", "demo")), "# demo\na.rs\n0 files");
+    }
+
+    #[test]
+    fn leaves_placeholders_in_values_alone() {
+        assert_eq!(fill("{{tree}} in {{root}}", &context("{{root}}/{{date}}.md", "demo")), "{{root}}/{{date}}.md in demo");
+    }
+
+    #[test]
+    fn keeps_unknown_and_unclosed_placeholders() {
+        assert_eq!(fill("{{nope}} {{{tree}} {{tree", &context("x", "demo")), "{{nope}} {x {{tree");
+    }
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19723), (2024, 1, 1));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+    }
+}
@@ -0,0 +1,49 @@
+//! `--timing`, how long the scan took and what each rule left out, for tuning ignore patterns on big trees.
+
+use std::time::Duration;
+
+/// Tallied during the scan, entries are counted where they were dropped so nothing below them is
+#[derive(Debug, Default)]
+pub struct Counters {
+    /// Directories listed
+    pub dirs: usize,
+    /// By `-i` and config paths
+    pub ignored_paths: usize,
+    /// By the default and config names
+    pub ignored_names: usize,
+    /// By `[rules]` ignore globs
+    pub ignored_by_rules: usize,
+    /// By `--dockerignore` or `--npm-pack`
+    pub excluded: usize,
+    /// Files without the `--contains` pattern, and directories left empty by it
+    pub not_containing: usize,
+    /// Directories not recursed into because of a stop list
+    pub stopped: usize,
+    /// Directories not recursed into because of `--depth`
+    pub depth_limited: usize,
+}
+
+impl Counters {
+    /// Report for stderr, categories that didn't leave anything out are skipped
+    pub fn report(&self, scan: Duration, total: Duration, shown: usize) -> String {
+        let mut report: String = format!(
+            "scanned {} directories in {:.3}s ({:.3}s in total), {shown} entries shown",
+            self.dirs,
+            scan.as_secs_f64(),
+            total.as_secs_f64(),
+        );
+        let categories: [(&str, usize); 7] = [
+            ("ignored by path", self.ignored_paths),
+            ("ignored by name", self.ignored_names),
+            ("ignored by [rules]", self.ignored_by_rules),
+            ("excluded by ignore file", self.excluded),
+            ("without --contains pattern", self.not_containing),
+            ("stopped directories", self.stopped),
+            ("directories past --depth", self.depth_limited),
+        ];
+        for (label, count) in categories.into_iter().filter(|&(_, count)| count > 0) {
+            report.push_str(&format!("\n  {label:<28} {count:>9}"));
+        }
+        report
+    }
+}
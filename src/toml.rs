@@ -0,0 +1,500 @@
+//! A small TOML reader for manifests and our own config file.
+//!
+//! Covers tables (`[a."b c"]`), dotted keys, strings (basic, literal and multiline), integers, floats,
+//! booleans, arrays, inline tables and arrays of tables (`[[bin]]`). Dates aren't supported.
+
+use std::collections::{BTreeMap, HashSet};
+
+pub type Table = BTreeMap<String, Value>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_table(&self) -> Option<&Table> {
+        match self {
+            Value::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+}
+
+/// Looks up a dotted path like `package.name`
+pub fn get<'a>(table: &'a Table, path: &str) -> Option<&'a Value> {
+    let mut keys = path.split('.');
+    let mut value: &Value = table.get(keys.next()?)?;
+    for key in keys {
+        value = value.as_table()?.get(key)?;
+    }
+    Some(value)
+}
+
+/// Parses a document, errors carry the line number
+pub fn parse(text: &str) -> Result<Table, String> {
+    let mut parser = Parser { chars: text.chars().collect(), pos: 0 };
+    parser.document().map_err(|err| format!("line {}: {err}", parser.line()))
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        self.chars[..self.pos.min(self.chars.len())].iter().filter(|&&c| c == '\n').count() + 1
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_str(&self, word: &str) -> bool {
+        word.chars().enumerate().all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c: Option<char> = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        match self.next() {
+            Some(found) if found == c => Ok(()),
+            Some(found) => Err(format!("expected `{c}`, found `{found}`")),
+            None => Err(format!("expected `{c}`, found end of file")),
+        }
+    }
+
+    /// Spaces and tabs only
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Spaces, newlines and comments
+    fn skip_blank(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\r' | '\n') => self.pos += 1,
+                Some('#') => self.skip_comment(),
+                _ => return,
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        while self.peek().is_some_and(|c| c != '\n') {
+            self.pos += 1;
+        }
+    }
+
+    fn document(&mut self) -> Result<Table, String> {
+        let mut root: Table = Table::new();
+        let mut current: Vec<String> = Vec::new();
+        //Each [table] can only be opened once
+        let mut headers: HashSet<Vec<String>> = HashSet::new();
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                None => return Ok(root),
+                Some('[') if self.peek_str("[[") => {
+                    self.pos += 2;
+                    current = self.key_path(']')?;
+                    self.expect(']')?;
+                    self.expect(']')?;
+                    //Tables below the previous element can be opened again in the new one
+                    headers.retain(|header| !header.starts_with(&current));
+                    let (key, parents) = current.split_last().ok_or("missing key")?;
+                    let parent: &mut Table = table_at(&mut root, parents)?;
+                    match parent.entry(key.clone()).or_insert_with(|| Value::Array(Vec::new())) {
+                        Value::Array(items) if items.iter().all(|item| matches!(item, Value::Table(_))) => items.push(Value::Table(Table::new())),
+                        _ => return Err(format!("`{key}` is not an array of tables")),
+                    }
+                }
+                Some('[') => {
+                    self.next();
+                    current = self.key_path(']')?;
+                    self.expect(']')?;
+                    if !headers.insert(current.clone()) {
+                        return Err(format!("duplicate table `{}`", current.join(".")));
+                    }
+                    let (key, parents) = current.split_last().ok_or("missing key")?;
+                    if matches!(table_at(&mut root, parents)?.get(key), Some(Value::Array(_))) {
+                        return Err(format!("`{key}` is an array of tables"));
+                    }
+                    table_at(&mut root, &current)?;
+                }
+                Some(_) => {
+                    let path: Vec<String> = self.key_path('=')?;
+                    self.expect('=')?;
+                    let value: Value = self.value()?;
+                    let (key, parents) = path.split_last().ok_or("missing key")?;
+                    let table: &mut Table = table_at(&mut root, &[current.as_slice(), parents].concat())?;
+                    if table.insert(key.clone(), value).is_some() {
+                        return Err(format!("duplicate key `{key}`"));
+                    }
+                }
+            }
+            self.skip_spaces();
+            if self.peek() == Some('#') {
+                self.skip_comment();
+            }
+            match self.peek() {
+                None | Some('\n') => {}
+                Some('\r') if self.chars.get(self.pos + 1) == Some(&'\n') => {}
+                Some(c) => return Err(format!("unexpected `{c}` after value")),
+            }
+        }
+    }
+
+    /// `a."b.c".d` up to (not including) `end`
+    fn key_path(&mut self, end: char) -> Result<Vec<String>, String> {
+        let mut path: Vec<String> = Vec::new();
+        loop {
+            self.skip_spaces();
+            let key: String = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let start: usize = self.pos;
+                    while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err(String::from("expected a key"));
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            path.push(key);
+            self.skip_spaces();
+            match self.peek() {
+                Some('.') => { self.next(); }
+                Some(c) if c == end => return Ok(path),
+                Some(c) => return Err(format!("unexpected `{c}` in key")),
+                None => return Err(String::from("unexpected end of file in key")),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_spaces();
+        match self.peek() {
+            Some('"') if self.peek_str("\"\"\"") => self.multiline_string("\"\"\"").map(Value::String),
+            Some('\'') if self.peek_str("'''") => self.multiline_string("'''").map(Value::String),
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(_) if self.peek_str("true") => { self.pos += 4; Ok(Value::Boolean(true)) }
+            Some(_) if self.peek_str("false") => { self.pos += 5; Ok(Value::Boolean(false)) }
+            Some(_) => self.number(),
+            None => Err(String::from("expected a value")),
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.next();
+        let mut items: Vec<Value> = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(']') {
+                self.next();
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(String::from("expected `,` or `]` in array")),
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, String> {
+        self.next();
+        let mut table: Table = Table::new();
+        loop {
+            self.skip_spaces();
+            if self.peek() == Some('}') {
+                self.next();
+                return Ok(Value::Table(table));
+            }
+            let path: Vec<String> = self.key_path('=')?;
+            self.expect('=')?;
+            let value: Value = self.value()?;
+            let (key, parents) = path.split_last().ok_or("missing key")?;
+            if table_at(&mut table, parents)?.insert(key.clone(), value).is_some() {
+                return Err(format!("duplicate key `{key}`"));
+            }
+            self.skip_spaces();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Table(table)),
+                _ => return Err(String::from("expected `,` or `}` in inline table")),
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.next();
+        let mut string: String = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.escape()?),
+                Some('\n') | None => return Err(String::from("unterminated string")),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.next();
+        let mut string: String = String::new();
+        loop {
+            match self.next() {
+                Some('\'') => return Ok(string),
+                Some('\n') | None => return Err(String::from("unterminated string")),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn multiline_string(&mut self, delimiter: &str) -> Result<String, String> {
+        self.pos += 3;
+        //A newline right after the opening delimiter is trimmed
+        if self.peek_str("\r\n") { self.pos += 2; } else if self.peek() == Some('\n') { self.pos += 1; }
+
+        let mut string: String = String::new();
+        loop {
+            if self.peek_str(delimiter) {
+                self.pos += 3;
+                return Ok(string);
+            }
+            match self.next() {
+                Some('\\') if delimiter == "\"\"\"" => {
+                    //Line ending backslash swallows the newline and leading whitespace
+                    if matches!(self.peek(), Some('\n' | '\r' | ' ' | '\t')) {
+                        self.skip_blank();
+                    } else {
+                        string.push(self.escape()?);
+                    }
+                }
+                Some(c) => string.push(c),
+                None => return Err(String::from("unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        match self.next() {
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some('b') => Ok('\u{8}'),
+            Some('f') => Ok('\u{c}'),
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some(c @ ('u' | 'U')) => {
+                let digits: usize = if c == 'u' { 4 } else { 8 };
+                let hex: String = (0..digits).filter_map(|_| self.next()).collect();
+                u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or(format!("invalid unicode escape `{hex}`"))
+            }
+            Some(c) => Err(format!("invalid escape `\\{c}`")),
+            None => Err(String::from("unterminated string")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start: usize = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || "+-._".contains(c)) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if text.is_empty() {
+            return Err(format!("expected a value, found `{}`", self.peek().unwrap_or_default()));
+        }
+        number(&text).ok_or_else(|| format!("invalid value `{text}`"))
+    }
+}
+
+/// Decimal, `0x`/`0o`/`0b` integers and floats with single `_` between digits, `inf` and `nan`
+fn number(text: &str) -> Option<Value> {
+    let unsigned: &str = text.strip_prefix(['+', '-']).unwrap_or(text);
+    match unsigned {
+        "inf" if text.starts_with('-') => return Some(Value::Float(f64::NEG_INFINITY)),
+        "inf" => return Some(Value::Float(f64::INFINITY)),
+        "nan" => return Some(Value::Float(f64::NAN)),
+        _ => {}
+    }
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(digits) = text.strip_prefix(prefix) {
+            return if digits_ok(digits, radix) { i64::from_str_radix(&digits.replace('_', ""), radix).ok().map(Value::Integer) } else { None };
+        }
+    }
+
+    //int [. digits] [e [sign] digits], a float with either of the last two
+    let (mantissa, exponent): (&str, Option<&str>) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent.strip_prefix(['+', '-']).unwrap_or(exponent))),
+        None => (unsigned, None),
+    };
+    let (integer, fraction): (&str, Option<&str>) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let leading_zero: bool = integer.len() > 1 && integer.starts_with('0');
+    if leading_zero || !digits_ok(integer, 10) || !fraction.map_or(true, |digits| digits_ok(digits, 10)) || !exponent.map_or(true, |digits| digits_ok(digits, 10)) {
+        return None;
+    }
+    let plain: String = text.replace('_', "");
+    if fraction.is_none() && exponent.is_none() {
+        plain.parse().ok().map(Value::Integer)
+    } else {
+        plain.parse().ok().map(Value::Float)
+    }
+}
+
+/// Digits in `radix`, with `_` only between two of them
+fn digits_ok(digits: &str, radix: u32) -> bool {
+    !digits.is_empty() && !digits.starts_with('_') && !digits.ends_with('_') && !digits.contains("__")
+        && digits.chars().all(|c| c == '_' || c.is_digit(radix))
+}
+
+/// The table at `path` below `root`, creating missing ones. An array of tables stands for its last element
+fn table_at<'a>(root: &'a mut Table, path: &[String]) -> Result<&'a mut Table, String> {
+    let mut table: &mut Table = root;
+    for key in path {
+        let value: &mut Value = table.entry(key.clone()).or_insert_with(|| Value::Table(Table::new()));
+        table = match value {
+            Value::Table(table) => table,
+            Value::Array(items) => match items.last_mut() {
+                Some(Value::Table(table)) => table,
+                _ => return Err(format!("`{key}` is not a table")),
+            },
+            _ => return Err(format!("`{key}` is not a table")),
+        };
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> Result<Value, String> {
+        parse(&format!("value = {text}")).map(|mut table| table.remove("value").unwrap())
+    }
+
+    fn string(text: &str) -> Result<Value, String> {
+        Ok(Value::String(String::from(text)))
+    }
+
+    #[test]
+    fn tables_and_keys() {
+        let table: Table = parse("top = 1\n[package]\nname = \"demo\" # comment\nmeta.tags = [\"a\", 'b']\n[\"a b\".c]\nd = true\n").unwrap();
+        assert_eq!(get(&table, "top"), Some(&Value::Integer(1)));
+        assert_eq!(get(&table, "package.name").and_then(Value::as_str), Some("demo"));
+        assert_eq!(get(&table, "package.meta.tags"), Some(&Value::Array(vec![Value::String(String::from("a")), Value::String(String::from("b"))])));
+        assert_eq!(table["a b"].as_table().and_then(|table| get(table, "c.d")), Some(&Value::Boolean(true)));
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(value(r#""tab\tquote\" \u00e9""#), string("tab\tquote\" é"));
+        assert_eq!(value(r"'C:\path'"), string(r"C:\path"));
+        assert_eq!(value("\"\"\"\nline one\nline two\"\"\""), string("line one\nline two"));
+        assert_eq!(value("\"\"\"one \\\n   two\"\"\""), string("one two"));
+        assert_eq!(value(r"'''raw \n'''"), string(r"raw \n"));
+        assert!(value("\"unterminated").is_err());
+        assert!(value(r#""bad \q escape""#).is_err());
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(value("42"), Ok(Value::Integer(42)));
+        assert_eq!(value("-17"), Ok(Value::Integer(-17)));
+        assert_eq!(value("+0"), Ok(Value::Integer(0)));
+        assert_eq!(value("1_000"), Ok(Value::Integer(1000)));
+        assert_eq!(value("0xff"), Ok(Value::Integer(255)));
+        assert_eq!(value("0o17"), Ok(Value::Integer(15)));
+        assert_eq!(value("0b101"), Ok(Value::Integer(5)));
+        assert_eq!(value("3.5"), Ok(Value::Float(3.5)));
+        assert_eq!(value("1e3"), Ok(Value::Float(1000.0)));
+        assert_eq!(value("-2.5E-1"), Ok(Value::Float(-0.25)));
+        assert_eq!(value("-inf"), Ok(Value::Float(f64::NEG_INFINITY)));
+        assert!(matches!(value("nan"), Ok(Value::Float(nan)) if nan.is_nan()));
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        for text in ["abc", "1__0", "_1", "1_", "01", "1.", ".5", "1e", "1.e5", "0x", "0xg", "+0xff", "infinity", "NaN", "1979-05-27", "99999999999999999999"] {
+            assert!(value(text).is_err(), "{text} should be rejected");
+        }
+    }
+
+    #[test]
+    fn arrays_and_inline_tables() {
+        let parsed: Value = value("{ name = \"x\", deps = [1, 2,\n 3,], nested.key = false }").unwrap();
+        let table: &Table = parsed.as_table().unwrap();
+        assert_eq!(table["deps"].as_array().map(<[Value]>::len), Some(3));
+        assert_eq!(get(table, "nested.key"), Some(&Value::Boolean(false)));
+        assert!(value("[1 2]").is_err());
+        assert!(value("{ a = 1, a = 2 }").is_err());
+    }
+
+    #[test]
+    fn arrays_of_tables() {
+        let table: Table = parse("[package]\nname = \"parser-core\"\ndescription = \"tokenizer and AST\"\n\n[[bin]]\nname = \"a\"\n[bin.meta]\nx = 1\n\n[[bin]]\nname = \"b\"\n[bin.meta]\nx = 2\n").unwrap();
+        assert_eq!(get(&table, "package.name").and_then(Value::as_str), Some("parser-core"));
+        assert_eq!(get(&table, "package.description").and_then(Value::as_str), Some("tokenizer and AST"));
+        let bins: &[Value] = table["bin"].as_array().unwrap();
+        assert_eq!(bins.len(), 2);
+        assert_eq!(bins[1].as_table().and_then(|bin| get(bin, "name")).and_then(Value::as_str), Some("b"));
+        assert_eq!(bins[1].as_table().and_then(|bin| get(bin, "meta.x")), Some(&Value::Integer(2)));
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        assert!(parse("a = 1\na = 2").is_err());
+        assert!(parse("[a]\nx = 1\n[a]\ny = 2").is_err());
+        assert!(parse("a = 1\n[a]").is_err());
+        assert!(parse("[a]\n[[a]]").is_err());
+        assert!(parse("[[a]]\n[a]").is_err());
+        assert!(parse("a = [1]\n[[a]]").is_err());
+        assert!(parse("a = true false").is_err());
+        assert!(parse("= 1").is_err());
+        assert!(parse("a = ").is_err());
+    }
+
+    #[test]
+    fn errors_carry_the_line() {
+        assert_eq!(parse("a = 1\n\nb = @").unwrap_err(), "line 3: expected a value, found `@`");
+        assert_eq!(parse("a = 1\nb = 1x").unwrap_err(), "line 2: invalid value `1x`");
+    }
+}
@@ -0,0 +1,49 @@
+//! `--treemap`, directory sizes as proportional bars for spotting what's eating the space.
+
+use crate::entry::Entry;
+use crate::size;
+use crate::style;
+
+const BAR_WIDTH: usize = 20;
+/// Entries under this share of the total are left out, so the map stays scannable
+const MIN_SHARE: f64 = 0.01;
+
+/// One line per entry taking up at least 1% of the total, biggest first, directories nested under their parent
+pub fn render(entries: &[Entry]) -> String {
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
+    let mut lines: Vec<String> = vec![format!("{} {:>4} {:>6}  .", bar(1.0), "100%", size::human(total))];
+    render_level(entries, total, 1, &mut lines);
+    lines.join("\n")
+}
+
+fn render_level(entries: &[Entry], total: u64, depth: usize, lines: &mut Vec<String>) {
+    let mut entries: Vec<&Entry> = entries.iter().collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.size));
+
+    for entry in entries {
+        let share: f64 = if total == 0 { 0.0 } else { entry.size as f64 / total as f64 };
+        if share < MIN_SHARE { break; }
+        lines.push(format!(
+            "{} {:>3.0}% {:>6}  {}{}",
+            bar(share),
+            share * 100.0,
+            size::human(entry.size),
+            "  ".repeat(depth),
+            style::escape_control(&entry.display_name(false)),
+        ));
+        render_level(&entry.children, total, depth + 1, lines);
+    }
+}
+
+/// Block characters in eighths, so small differences still show
+fn bar(share: f64) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+    let eighths: usize = (share * (BAR_WIDTH * 8) as f64).round() as usize;
+    let mut bar: String = "█".repeat(eighths / 8);
+    if eighths % 8 > 0 {
+        bar.push(PARTIAL[eighths % 8]);
+    }
+    let padding: usize = BAR_WIDTH - bar.chars().count();
+    format!("[{bar}{}]", " ".repeat(padding))
+}
@@ -0,0 +1,137 @@
+//! Just enough terminal handling for the interactive modes: raw keyboard input and redrawing a full screen.
+//!
+//! Only unix terminals are supported, through termios on `/dev/tty` so it works with stdout redirected.
+
+#[cfg(not(unix))]
+use std::io;
+
+pub enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Escape,
+    Backspace,
+    Char(char),
+}
+
+#[cfg(unix)]
+pub use unix::Terminal;
+
+#[cfg(unix)]
+mod unix {
+    use super::Key;
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Read, Write};
+    use std::os::unix::io::AsRawFd;
+
+    /// The terminal in raw mode on the alternate screen, restored when dropped
+    pub struct Terminal {
+        tty: File,
+        original: libc::termios,
+    }
+
+    impl Terminal {
+        pub fn open() -> io::Result<Terminal> {
+            let tty: File = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+            //SAFETY: termios is plain data, filled in by tcgetattr before use
+            let mut original: libc::termios = unsafe { std::mem::zeroed() };
+            if unsafe { libc::tcgetattr(tty.as_raw_fd(), &mut original) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut raw: libc::termios = original;
+            unsafe { libc::cfmakeraw(&mut raw) };
+            //Reads give up after 100ms, so a lone Esc can be told apart from an escape sequence
+            raw.c_cc[libc::VMIN] = 0;
+            raw.c_cc[libc::VTIME] = 1;
+            if unsafe { libc::tcsetattr(tty.as_raw_fd(), libc::TCSANOW, &raw) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut terminal: Terminal = Terminal { tty, original };
+            terminal.tty.write_all(b"\x1b[?1049h\x1b[?25l")?;
+            Ok(terminal)
+        }
+
+        /// Columns and rows
+        pub fn size(&self) -> (usize, usize) {
+            //SAFETY: TIOCGWINSZ only writes into the winsize we pass
+            let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+            let ok: bool = unsafe { libc::ioctl(self.tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } == 0;
+            if ok && size.ws_col > 0 && size.ws_row > 0 { (size.ws_col as usize, size.ws_row as usize) } else { (80, 24) }
+        }
+
+        /// Replaces the screen with `lines`
+        pub fn draw(&mut self, lines: &[String]) -> io::Result<()> {
+            let screen: String = format!("\x1b[H\x1b[2J{}", lines.join("\r\n"));
+            self.tty.write_all(screen.as_bytes())?;
+            self.tty.flush()
+        }
+
+        pub fn read_key(&mut self) -> io::Result<Key> {
+            loop {
+                let Some(byte) = self.read_byte()? else { continue };
+                let key: Key = match byte {
+                    b'\r' | b'\n' => Key::Enter,
+                    0x7f | 0x08 => Key::Backspace,
+                    0x1b => match (self.read_byte()?, self.read_byte()?) {
+                        (Some(b'[') | Some(b'O'), Some(b'A')) => Key::Up,
+                        (Some(b'[') | Some(b'O'), Some(b'B')) => Key::Down,
+                        (Some(b'[') | Some(b'O'), Some(b'H')) => Key::Home,
+                        (Some(b'[') | Some(b'O'), Some(b'F')) => Key::End,
+                        (Some(b'['), Some(code @ (b'5' | b'6'))) => {
+                            //The trailing ~
+                            self.read_byte()?;
+                            if code == b'5' { Key::PageUp } else { Key::PageDown }
+                        }
+                        (None, _) => Key::Escape,
+                        _ => continue,
+                    },
+                    //Ctrl-C doesn't raise SIGINT in raw mode
+                    0x03 => Key::Escape,
+                    byte if byte.is_ascii() => Key::Char(byte as char),
+                    _ => continue,
+                };
+                return Ok(key);
+            }
+        }
+
+        fn read_byte(&mut self) -> io::Result<Option<u8>> {
+            let mut byte: [u8; 1] = [0];
+            Ok((self.tty.read(&mut byte)? == 1).then_some(byte[0]))
+        }
+    }
+
+    impl Drop for Terminal {
+        fn drop(&mut self) {
+            let _ = self.tty.write_all(b"\x1b[?25h\x1b[?1049l");
+            //SAFETY: restoring the settings read in open()
+            unsafe { libc::tcsetattr(self.tty.as_raw_fd(), libc::TCSANOW, &self.original) };
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub struct Terminal;
+
+#[cfg(not(unix))]
+impl Terminal {
+    pub fn open() -> io::Result<Terminal> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "interactive mode needs a unix terminal"))
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (80, 24)
+    }
+
+    pub fn draw(&mut self, _lines: &[String]) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub fn read_key(&mut self) -> io::Result<Key> {
+        Ok(Key::Escape)
+    }
+}
@@ -0,0 +1,101 @@
+//! `project-tree verify`, checking the scanned tree against a declared structure.
+//!
+//! The spec is either a TOML file with `required` and `forbidden` glob lists, or a rendered tree
+//! (anything `scaffold` reads) whose every entry is required.
+
+use crate::entry::Entry;
+use crate::glob;
+use crate::scaffold::{self, Node};
+use crate::toml::{self, Table};
+use std::io;
+use std::path::Path;
+
+/// Patterns without a `/` match names at any depth, the others paths from the root. A trailing `/` only matches directories
+#[derive(Debug, Default)]
+pub struct Spec {
+    /// Each has to match at least one entry
+    pub required: Vec<String>,
+    /// None may match anything
+    pub forbidden: Vec<String>,
+}
+
+impl Spec {
+    pub fn parse(text: &str, is_toml: bool) -> Result<Spec, String> {
+        if !is_toml {
+            let mut required: Vec<String> = Vec::new();
+            tree_paths(&scaffold::parse(text)?, "", &mut required);
+            return Ok(Spec { required, forbidden: Vec::new() });
+        }
+
+        let table: Table = toml::parse(text)?;
+        let list = |key: &str| -> Result<Vec<String>, String> {
+            let Some(value) = table.get(key) else { return Ok(Vec::new()) };
+            value.as_array()
+                .and_then(|items| items.iter().map(|item| item.as_str().map(str::to_owned)).collect())
+                .ok_or_else(|| format!("`{key}` must be an array of strings"))
+        };
+        Ok(Spec { required: list("required")?, forbidden: list("forbidden")? })
+    }
+
+    /// Reads `path`, as TOML when it ends in `.toml`
+    pub fn load(path: &Path, text: &str) -> io::Result<Spec> {
+        let is_toml: bool = path.extension().is_some_and(|extension| extension == "toml");
+        Spec::parse(text, is_toml).map_err(|message| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {message}", path.display())))
+    }
+
+    /// One line per violation, empty when the tree complies
+    pub fn check(&self, entries: &[Entry]) -> Vec<String> {
+        let mut paths: Vec<(&str, bool)> = Vec::new();
+        collect(entries, &mut paths);
+
+        let mut violations: Vec<String> = Vec::new();
+        for pattern in &self.required {
+            if !paths.iter().any(|&(path, is_dir)| matches(pattern, path, is_dir)) {
+                violations.push(format!("missing: {pattern}"));
+            }
+        }
+        for pattern in &self.forbidden {
+            for &(path, is_dir) in paths.iter().filter(|&&(path, is_dir)| matches(pattern, path, is_dir)) {
+                violations.push(format!("forbidden: {path}{} (matches {pattern})", if is_dir { "/" } else { "" }));
+            }
+        }
+        violations
+    }
+}
+
+fn matches(pattern: &str, path: &str, is_dir: bool) -> bool {
+    let (pattern, dir_only) = match pattern.strip_suffix('/') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    if dir_only && !is_dir { return false; }
+    if pattern.contains('/') {
+        glob::matches(pattern.trim_start_matches("./"), path)
+    } else {
+        glob::matches(pattern, path.rsplit('/').next().unwrap_or(path))
+    }
+}
+
+fn collect<'a>(entries: &'a [Entry], paths: &mut Vec<(&'a str, bool)>) {
+    for entry in entries {
+        paths.push((&entry.path, entry.is_dir));
+        collect(&entry.children, paths);
+    }
+}
+
+/// Paths from the root, directories with a trailing `/` so a file can't stand in for them.
+/// Glob characters in names are escaped, the tree lists literal paths
+fn tree_paths(nodes: &[Node], parent: &str, paths: &mut Vec<String>) {
+    for node in nodes {
+        let name: String = node.name.chars().fold(String::new(), |mut escaped, c| {
+            if "*?[\\".contains(c) { escaped.push('\\'); }
+            escaped.push(c);
+            escaped
+        });
+        let path: String = if node.name.contains('/') || parent.is_empty() { name } else { format!("{parent}/{name}") };
+        //`./` anchors top level entries to the root too
+        let anchored: String = if path.contains('/') { path.clone() } else { format!("./{path}") };
+        paths.push(if node.is_dir { format!("{anchored}/") } else { anchored });
+        tree_paths(&node.children, &path, paths);
+    }
+}